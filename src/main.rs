@@ -1,19 +1,25 @@
 use dotenv::dotenv;
 use polymarket_rs_client::{ClobClient, SigType};
 use std::env;
+use std::path::PathBuf;
 use std::sync::Arc;
 use tokio::signal;
 use tokio::sync::{mpsc, Mutex};
 
 mod modules;
 use modules::{
-    data::{fetch_current_positions, fetch_open_orders},
-    execution::order_execution_task,
+    config::{config_watch_task, load_config, DEFAULT_CONFIG_PATH},
+    data::fetch_open_orders,
+    execution::{order_execution_task, LiveClobBackend},
+    heartbeat::heartbeat_task,
+    market_manager::{market_manager_task, MarketSpec},
     monitor::monitor_task,
+    paper::{paper_matching_task, PaperBackend},
     persistence::{load_state, save_state},
-    split_merge::{execute_split_merge, TransactionType},
+    strategy::StrategyEvent,
     trading::trading_logic_task,
-    types::{BotCommand, MarketUpdate},
+    types::{command_channel, BotCommand, MarketUpdate},
+    unwind::unwind_signal_task,
     user_ws::user_ws_task,
     websocket::websocket_client_task,
 };
@@ -27,6 +33,7 @@ const POLYGON: u64 = 137;
 // These buffer sizes are tuned for the expected message rates and API limits
 const MARKET_CHANNEL_CAP: usize = 1024; // Market updates: handles burst of price changes
 const COMMAND_CHANNEL_CAP: usize = 1024; // Trading commands: matches Polymarket API rate limits
+const EVENT_CHANNEL_CAP: usize = 1024; // Fills and order events for the strategy
 
 /// Polymarket Market‑Making Bot — High‑Level Architecture
 ///
@@ -48,44 +55,63 @@ const COMMAND_CHANNEL_CAP: usize = 1024; // Trading commands: matches Polymarket
 ///   - `price_change` (new schema) with per‑asset `best_bid`/`best_ask` for fast deltas
 /// - User WS (`user_ws.rs`): order updates and fills → inventory sync
 /// - REST (`execution.rs`):
-///   - `/positions?user=` on startup to seed inventory (persist positions across restarts)
+///   - `/positions?user=` when a market starts to seed inventory (persist positions across restarts)
 ///   - `get_orders` for optional open‑order debugging
 ///
-/// Strategy (trading.rs):
+/// Markets (market_manager.rs):
+/// - Every `[markets.<yes_token>]` section with `condition_id` and `no_token` runs, plus the
+///   market pinned by env `CONDITIONID`/`ASSETS_IDS`; config reloads start and stop markets
+/// - All markets share the feeds and the trading and execution tasks; `[portfolio.group]` and
+///   `[portfolio.account]` limit them together (limits.rs)
+///
+/// Strategy (trading.rs runs one instance per market of the `Strategy` named in its config; default `edge` in edge_strategy.rs):
 /// - Quotes anchor to book edges, not mid:
 ///   - Bid: 10% below current best bid
 ///   - Ask: 10% above current best ask (scaled toward 1.0)
-/// - Asymmetrical skew only when dollar inventory imbalance > MAX_INVENTORY_IMBALANCE:
+/// - Inventory skew along a linear/exponential curve of the dollar imbalance (skew.rs):
 ///   - Long YES: lower ask (sell faster), lower bid (discourage buying more YES)
 ///   - Short YES: raise bid (buy faster), raise ask (discourage selling more YES)
-/// - Conditional replace: cancel and re‑quote only if mid‑price bucket changed
+/// - Conditional replace: re‑quote only if mid‑price bucket or skew changed
+///   - trading.rs diffs the desired quotes against open orders and only cancels/creates the difference
 ///   - Bucket = ceil(mid * 100) → reduces churn on tiny price moves
+/// - Alternative `avellaneda` (avellaneda.rs): reservation price and spread from inventory,
+///   risk aversion, estimated volatility, order arrival intensity and time to end date
 /// - Risk guards:
-///   - Pause and cancel all if total position dollars > MAX_POSITION_SIZE
-///   - Pause if prices are extreme (bid ≤ 0.02 or ask ≥ 0.98)
+///   - Market / event group / account limits on unpaired notional and shares, open notional
+///     and daily loss; a breach goes reduce-only or pauses (limits.rs)
+///   - Pause a market if its prices are extreme (bid ≤ 0.02 or ask ≥ 0.98); each pause reason
+///     resumes on its own condition after a cool-down (pause.rs)
+/// - Provisioning (provision.rs): splits only the USDC shortfall against the `[provision]`
+///   target so sell quotes can be posted, skipping it when on-chain balances cover it
+/// - Auto-merge (auto_merge.rs): free YES/NO pairs above `[merge] min_pairs` are merged back
+///   to USDC while running
+/// - Unwind (unwind.rs): `[unwind] enabled` or SIGUSR1 (SIGUSR2 to stop) sells each market's
+///   unpaired inventory, passively first, then stepping down to a price limit by a deadline
 ///
 /// Channels:
 /// - market_tx/rx (WebSocket → Logic): real‑time `MarketUpdate`
-/// - cmd_tx/rx (Logic → Execution): `BotCommand::{Create,Cancel,CancelAll,Shutdown}`
+/// - cmd_tx/rx (Logic → Execution): `BotCommand::{Create,Cancel,CancelAll,Merge,Split,Shutdown}`, stamped on send
+/// - event_tx/rx (User WS / paper → Logic): `StrategyEvent::{Fill,Order}`
 ///
 /// Shared State (Arc<Mutex<AppState>>):
 /// - my_open_orders: bot’s active orders
 /// - inventory: token_id → quantity
-/// - token_pairs/yes_token: explicit YES/NO mapping (1st/2nd in env `ASSETS_IDS`)
+/// - markets/token_pairs: running markets by YES token and the YES/NO mapping
 /// - last_prices: latest (bid, ask, ts) per token
 /// - last_mid_bucket: ceil(mid*100) per market for conditional re‑quotes
 /// - risk_paused / shutting_down: control flags
-/// - max_inventory_imbalance / max_position_size: dollar risk limits
+/// - config: per-market sizes, edges, skew, risk limits and tick handling from `CONFIG_PATH`
+///   (default config.toml), hot-reloaded on change
 ///
 /// Kill switch:
-/// - Ctrl+C sets `shutting_down`, issues `CancelAll`, and stops tasks cleanly.
+/// - Ctrl+C sets `shutting_down`, issues `CancelAll`, merges free pairs of every market, and
+///   stops tasks cleanly.
 #[tokio::main]
 async fn main() {
     dotenv().ok();
 
     // --- Initialize Polymarket client with proxy wallet (L2 headers proxy) ---
     let private_key = env::var("PK").expect("PK environment variable not set");
-    let condition_id = env::var("CONDITIONID").expect("CONDITIONID environment variable not set");
     let nonce = None;
     let client_l1 = ClobClient::with_l1_headers(HOST_PM, &private_key, POLYGON);
     let keys = client_l1
@@ -112,24 +138,40 @@ async fn main() {
     // (ws_* variables already captured above)
     let client_pm = Arc::new(Mutex::new(client_pm));
 
-    // Assets to subscribe (comma-separated ASSETS_IDS in env) - should be exactly 2 tokens (yes/no pair)
-    // let assets_ids: Vec<String> = env::var("ASSETS_IDS")
-    //     .unwrap_or_default()
-    //     .split(',')
-    //     .filter_map(|s| {
-    //         let t = s.trim();
-    //         if t.is_empty() {
-    //             None
-    //         } else {
-    //             Some(t.to_string())
-    //         }
-    //     })
-    //     .collect();
+    // Optional single market from the env (comma-separated ASSETS_IDS = YES,NO); more markets
+    // come from the config's [markets.*] sections
+    let assets_ids: Vec<String> = env::var("ASSETS_IDS")
+        .unwrap_or_default()
+        .split(',')
+        .filter_map(|s| {
+            let t = s.trim();
+            if t.is_empty() {
+                None
+            } else {
+                Some(t.to_string())
+            }
+        })
+        .collect();
+    let pinned_market = env::var("CONDITIONID").ok().map(|condition_id| {
+        if assets_ids.len() != 2 {
+            panic!("ASSETS_IDS must contain exactly 2 tokens: YES,NO");
+        }
+        // Explicit mapping: first token = YES, second token = NO
+        MarketSpec {
+            condition_id,
+            yes_token: assets_ids[0].clone(),
+            no_token: assets_ids[1].clone(),
+        }
+    });
 
-    let assets_id: String = env::var("ASSETS_IDS").unwrap_or_default();
-    // if assets_id.len() != 1 {
-    //     panic!("ASSETS_IDS must contain exactly 1 token");
-    // }
+    // Execution mode: "live" posts to the CLOB, "paper" simulates fills against the live book
+    let paper_mode = env::var("EXECUTION_MODE")
+        .map(|m| m.eq_ignore_ascii_case("paper"))
+        .unwrap_or(false);
+    println!(
+        "Execution mode: {}",
+        if paper_mode { "paper" } else { "live" }
+    );
 
     // Initialize state with token pairs and risk parameters (load snapshot if present)
     let mut initial_state = load_state().unwrap_or_default();
@@ -137,51 +179,30 @@ async fn main() {
     initial_state.shutting_down = false;
     initial_state.risk_paused = false;
 
-    // Explicit mapping: first token = YES, second token = NO
-    let yes_token = assets_id.clone();
-    // let no_token = assets_ids[1].clone();
-    // initial_state
-    //     .token_pairs
-    //     .insert(yes_token.clone(), no_token.clone());
-    // initial_state
-    //     .token_pairs
-    //     .insert(no_token.clone(), yes_token.clone());
-
-    // Store the explicit YES token for reference (clone to avoid moving)
-    initial_state.yes_token = Some(yes_token.clone());
-    initial_state.max_inventory_imbalance = env::var("MAX_INVENTORY_IMBALANCE")
-        .unwrap_or_else(|_| "25.0".to_string())
-        .parse()
-        .unwrap_or(25.0);
-    initial_state.max_position_size = env::var("MAX_POSITION_SIZE")
-        .unwrap_or_else(|_| "50.0".to_string())
+    // Markets, strategy and risk parameters: validated here, then hot-reloaded by config_watch_task
+    let config_path =
+        PathBuf::from(env::var("CONFIG_PATH").unwrap_or_else(|_| DEFAULT_CONFIG_PATH.to_string()));
+    initial_state.config = load_config(&config_path);
+    let mut markets = initial_state.config.market_specs();
+    markets.extend(pinned_market.clone());
+    if markets.is_empty() {
+        println!("Warning: No markets configured; set CONDITIONID/ASSETS_IDS or add [markets.*] sections with condition_id and no_token");
+    }
+    for market in &markets {
+        println!(
+            "Market {} config: {:?}",
+            market.condition_id,
+            initial_state.market_config(&market.yes_token)
+        );
+    }
+    // Dead-man switch: orders are GTD and renewed while healthy (0 = plain GTC)
+    initial_state.order_ttl_secs = env::var("ORDER_TTL_SECS")
+        .unwrap_or_else(|_| "300".to_string())
         .parse()
-        .unwrap_or(50.0);
+        .unwrap_or(300);
 
-    // Ensure inventory keys exist
-    initial_state
-        .inventory
-        .entry(assets_id.clone())
-        .or_insert(0.0);
-    // initial_state
-    //     .inventory
-    //     .entry(assets_ids[1].clone())
-    //     .or_insert(0.0);
-
-    // Fetch current positions from Polymarket API
-    println!("Fetching current positions from Polymarket...");
-    match fetch_current_positions(&client_pm, &assets_id, &condition_id).await {
-        Ok(positions) => {
-            println!("Current positions: {:?}", positions);
-            for (token_id, quantity) in positions {
-                initial_state.inventory.insert(token_id, quantity);
-            }
-        }
-        Err(e) => {
-            println!("Warning: Could not fetch current positions: {:?}", e);
-            println!("Starting with zero inventory. Manual position sync recommended.");
-        }
-    }
+    // Positions and metadata (end date, game start, reward terms) are fetched per market by
+    // the market manager as each one starts
 
     // Also fetch open orders for debugging
     // println!("Fetching open orders from Polymarket...");
@@ -195,53 +216,8 @@ async fn main() {
     //     }
     // }
 
-    // Execute split/merge transaction
-    let amount = "500"; // Amount in USDC
-    let neg_risk = false; // Set to true if using neg risk adapter
-
-    // split transaction
-    match execute_split_merge(
-        TransactionType::Split,
-        amount,
-        &condition_id,
-        &proxy_wallet,
-        neg_risk,
-    )
-    .await
-    {
-        Ok(tx_hash) => {
-            println!("Split transaction successful: {:?}", tx_hash);
-            // if let Some(k) = initial_state.token_pairs.get(&yes_token).cloned() {
-            initial_state
-                .inventory
-                .insert(yes_token, amount.parse::<f64>().unwrap());
-            // }
-            // if let Some(k) = initial_state.token_pairs.get(&no_token).cloned() {
-            //     initial_state
-            //         .inventory
-            //         .insert(k, amount.parse::<f64>().unwrap());
-            // }
-        }
-        Err(e) => println!("Split transaction failed: {:?}", e),
-    }
-
-    // initial_state
-    //     .inventory
-    //     .insert(yes_token, amount.parse::<f64>().unwrap());
-
-    // Example merge transaction
-    // match execute_split_merge(
-    //     TransactionType::Merge,
-    //     amount,
-    //     &condition_id,
-    //     &proxy_wallet,
-    //     neg_risk,
-    // )
-    // .await
-    // {
-    //     Ok(tx_hash) => println!("Merge transaction successful: {:?}", tx_hash),
-    //     Err(e) => println!("Merge transaction failed: {:?}", e),
-    // }
+    // YES/NO inventory for sell quotes is provisioned per market by provision_task, which
+    // splits only the shortfall against the `[provision]` target once the market is running
 
     // --- Shared state and channels ---
     let state = Arc::new(Mutex::new(initial_state));
@@ -258,31 +234,50 @@ async fn main() {
     // Data: BotCommand (Create/Cancel/CancelAll/OrderFilled/Shutdown)
     // Flow: trading_logic_task → order_execution_task
     // Capacity: 1024 commands (matches API rate limits)
-    let (cmd_tx, cmd_rx) = mpsc::channel::<BotCommand>(COMMAND_CHANNEL_CAP);
+    // Every command is stamped on send so execution can measure queue and ack latency
+    let (cmd_tx, cmd_rx) = command_channel(COMMAND_CHANNEL_CAP);
+
+    // Channel 3: Strategy Events (User WS / paper matching → Trading Logic)
+    // Purpose: Lets the strategy react to fills and order lifecycle events
+    // Data: StrategyEvent (Fill/Order)
+    let (event_tx, event_rx) = mpsc::channel::<StrategyEvent>(EVENT_CHANNEL_CAP);
 
     println!("Spawning tasks...");
 
     // --- Spawn tasks ---
     let ws_state = Arc::clone(&state);
     let ws_tx = market_tx.clone();
-    let ws_asset_id = assets_id.clone();
     let ws_handle = tokio::spawn(async move {
-        websocket_client_task(WS_MARKET_URL.to_string(), ws_asset_id, ws_tx, ws_state).await;
+        websocket_client_task(WS_MARKET_URL.to_string(), ws_tx, ws_state).await;
     });
 
     println!("Spawning trading logic task...");
     let logic_state = Arc::clone(&state);
     let logic_cmd_tx = cmd_tx.clone();
     let logic_handle = tokio::spawn(async move {
-        trading_logic_task(market_rx, logic_cmd_tx, logic_state).await;
+        trading_logic_task(market_rx, event_rx, logic_cmd_tx, logic_state).await;
     });
 
     println!("Spawning order execution task...");
     let exec_state = Arc::clone(&state);
-    let exec_client = Arc::clone(&client_pm);
-    let exec_handle = tokio::spawn(async move {
-        order_execution_task(cmd_rx, exec_client, exec_state).await;
-    });
+    let exec_handle = if paper_mode {
+        let backend = PaperBackend::new();
+        let paper_book = backend.book();
+        let paper_state = Arc::clone(&state);
+        let paper_cmd_tx = cmd_tx.clone();
+        let paper_event_tx = event_tx.clone();
+        tokio::spawn(async move {
+            paper_matching_task(paper_book, paper_state, paper_cmd_tx, paper_event_tx).await;
+        });
+        tokio::spawn(async move {
+            order_execution_task(cmd_rx, backend, exec_state).await;
+        })
+    } else {
+        let backend = LiveClobBackend::new(Arc::clone(&client_pm));
+        tokio::spawn(async move {
+            order_execution_task(cmd_rx, backend, exec_state).await;
+        })
+    };
 
     // --- Monitor task ---
     println!("Spawning monitor task...");
@@ -290,6 +285,42 @@ async fn main() {
     let monitor_handle = tokio::spawn(async move {
         monitor_task(monitor_state).await;
     });
+    // --- Config hot reload task ---
+    println!("Spawning config watch task...");
+    let config_state = Arc::clone(&state);
+    tokio::spawn(async move {
+        config_watch_task(config_path, config_state).await;
+    });
+    // --- Heartbeat / dead-man switch task ---
+    println!("Spawning heartbeat task...");
+    let heartbeat_state = Arc::clone(&state);
+    let heartbeat_cmd_tx = cmd_tx.clone();
+    tokio::spawn(async move {
+        heartbeat_task(heartbeat_state, heartbeat_cmd_tx).await;
+    });
+    // --- Unwind control signals ---
+    println!("Spawning unwind signal task...");
+    let unwind_state = Arc::clone(&state);
+    tokio::spawn(async move {
+        unwind_signal_task(unwind_state).await;
+    });
+    // --- Market manager task (per-market provisioning and auto-merge) ---
+    println!("Spawning market manager task...");
+    let manager_state = Arc::clone(&state);
+    let manager_cmd_tx = cmd_tx.clone();
+    let manager_proxy_wallet = proxy_wallet.clone();
+    let manager_client = Arc::clone(&client_pm);
+    tokio::spawn(async move {
+        market_manager_task(
+            manager_state,
+            manager_cmd_tx,
+            manager_proxy_wallet,
+            manager_client,
+            paper_mode,
+            pinned_market,
+        )
+        .await;
+    });
     // --- User WS task (authenticated) ---
     println!("Spawning user WS task...");
     let user_state = Arc::clone(&state);
@@ -301,6 +332,7 @@ async fn main() {
             ws_api_passphrase,
             user_state,
             user_cmd_tx,
+            event_tx,
         )
         .await;
     });
@@ -319,9 +351,7 @@ async fn main() {
     }
     println!("Sending cancel all and shutdown commands...");
     let _ = cmd_tx.send(BotCommand::CancelAll).await;
-    let _ = cmd_tx
-        .send(BotCommand::Shutdown(condition_id, proxy_wallet))
-        .await;
+    let _ = cmd_tx.send(BotCommand::Shutdown(proxy_wallet)).await;
     drop(market_tx);
     drop(cmd_tx);
 
@@ -0,0 +1,102 @@
+use crate::modules::logger;
+use crate::modules::market_manager::MarketSpec;
+use crate::modules::provision;
+use crate::modules::types::{AppState, BotCommand, CommandSender, Side};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tokio::time::Duration;
+
+// -------------------- Auto-Merge Defaults --------------------
+const DEFAULT_MIN_PAIRS: f64 = 50.0; // don't pay gas for fewer pairs than this
+const DEFAULT_KEEP_PAIRS: f64 = 0.0;
+const DEFAULT_INTERVAL_SECS: u64 = 30;
+
+/// `merge` in the config
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MergeConfig {
+    pub enabled: bool,   // off by default: merging sends on-chain transactions
+    pub min_pairs: f64,  // merge once at least this many free pairs are held
+    pub keep_pairs: f64, // pairs left unmerged for sell quotes (at least the provisioning target)
+    pub interval_secs: u64,
+}
+
+impl Default for MergeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            min_pairs: DEFAULT_MIN_PAIRS,
+            keep_pairs: DEFAULT_KEEP_PAIRS,
+            interval_secs: DEFAULT_INTERVAL_SECS,
+        }
+    }
+}
+
+/// Whole YES/NO pairs of `yes_token`'s market held and not committed to resting sells
+/// or to a merge already in flight
+pub fn free_pairs(s: &AppState, yes_token: &str) -> f64 {
+    let Some(no_token) = s.token_pairs.get(yes_token) else {
+        return 0.0;
+    };
+    let reserved = s.merge_reserved(yes_token);
+    let free = |token: &str| {
+        let held = s.inventory.get(token).copied().unwrap_or(0.0);
+        let selling: f64 = s
+            .my_open_orders
+            .values()
+            .filter(|o| o.asset_id == token && o.side == Side::Sell)
+            .map(|o| o.size)
+            .sum();
+        (held - selling - reserved).max(0.0)
+    };
+    free(yes_token).min(free(no_token)).floor()
+}
+
+// -------------------- Auto-Merge Task --------------------
+/// Periodically merges one market's paired YES/NO inventory back into USDC through the Safe
+/// while the bot runs. The shares are reserved (excluded from sell quotes) until execution
+/// reports the merge done, which is when inventory is reduced.
+pub async fn auto_merge_task(
+    state: Arc<Mutex<AppState>>,
+    cmd_tx: CommandSender,
+    market: MarketSpec,
+    proxy_wallet: String,
+) {
+    let yes_token = &market.yes_token;
+    let mut interval_secs = DEFAULT_INTERVAL_SECS;
+    loop {
+        tokio::time::sleep(Duration::from_secs(interval_secs)).await;
+        let shares = {
+            let mut s = state.lock().await;
+            if s.shutting_down {
+                break;
+            }
+            let market_config = s.market_config(yes_token);
+            let config = market_config.merge.clone();
+            // Never merge away what provisioning would split right back
+            let keep = config
+                .keep_pairs
+                .max(provision::target_shares(market_config));
+            interval_secs = config.interval_secs;
+            let busy = s.merge_reserved(yes_token) > 0.0 || s.split_pending.contains_key(yes_token);
+            if !config.enabled || busy {
+                continue;
+            }
+            let pairs = (free_pairs(&s, yes_token) - keep).floor();
+            if pairs < config.min_pairs.max(1.0) {
+                continue;
+            }
+            s.merge_reserved.insert(yes_token.clone(), pairs);
+            logger::logln(format!(
+                "Merge: {} free YES/NO pairs held in {}; merging back to USDC",
+                pairs, market.condition_id
+            ));
+            pairs as i64
+        };
+        let cmd = BotCommand::Merge(market.clone(), proxy_wallet.clone(), shares);
+        if cmd_tx.send(cmd).await.is_err() {
+            return;
+        }
+    }
+}
@@ -0,0 +1,183 @@
+use crate::modules::ladder::yes_ladder;
+use crate::modules::logger;
+use crate::modules::markout;
+use crate::modules::pause;
+use crate::modules::rewards;
+use crate::modules::strategy::{yes_side, Strategy, StrategyAction, YesBook, STRATEGY_AVELLANEDA};
+use crate::modules::types::{AppState, MarketUpdate, Side};
+use crate::modules::wind_down;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+// -------------------- Avellaneda-Stoikov Defaults --------------------
+const DEFAULT_GAMMA: f64 = 1.0; // risk aversion
+const DEFAULT_KAPPA: f64 = 100.0; // order arrival decay per 1.00 of distance from mid, until estimated
+const DEFAULT_VOLATILITY: f64 = 0.05; // YES price stdev per sqrt(day), until estimated
+const DEFAULT_MAX_HORIZON_DAYS: f64 = 7.0;
+const SECS_PER_DAY: f64 = 86_400.0;
+const MINUTES_PER_DAY: f64 = 1_440.0;
+
+/// `avellaneda` in the config
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AvellanedaConfig {
+    pub gamma: f64,
+    pub kappa: f64,            // fallback order arrival decay
+    pub volatility: f64,       // fallback sigma per sqrt(day)
+    pub max_horizon_days: f64, // caps (T - t) for far-dated markets or when end_date is unset
+}
+
+impl Default for AvellanedaConfig {
+    fn default() -> Self {
+        Self {
+            gamma: DEFAULT_GAMMA,
+            kappa: DEFAULT_KAPPA,
+            volatility: DEFAULT_VOLATILITY,
+            max_horizon_days: DEFAULT_MAX_HORIZON_DAYS,
+        }
+    }
+}
+
+// -------------------- Avellaneda-Stoikov Market Maker --------------------
+/// Quotes around a reservation price instead of the book edges:
+///
+/// - reservation r = mid − q·γ·σ²·τ, with q the YES−NO inventory in order-size lots
+/// - total spread δ = γ·σ²·τ + (2/γ)·ln(1 + γ/κ), quoted as r ± δ/2
+///
+/// τ is the time in days to the market's cutoff (the earlier of its end date and game
+/// start, capped at `max_horizon_days`), σ the shared realized volatility estimate rescaled
+/// to sqrt(day), and κ the order arrival decay estimated from how far trades print from the
+/// mid; each falls back to its config value until `[volatility]` has enough samples. Quotes are re-priced on
+/// every update and every timer tick; the trading task only replaces orders whose price
+/// actually changed. The wind-down schedule shrinks sizes and widens the spread on top.
+pub struct AvellanedaStoikov {
+    last_book: Option<YesBook>,
+    last_quotes: Option<(f64, f64)>, // only log when the quotes move
+}
+
+impl Default for AvellanedaStoikov {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AvellanedaStoikov {
+    pub fn new() -> Self {
+        Self {
+            last_book: None,
+            last_quotes: None,
+        }
+    }
+
+    fn quote(&mut self, book: &YesBook, s: &AppState) -> StrategyAction {
+        let config = s.market_config(&book.yes_token);
+        // Pull quotes on extreme prices without pausing; they return with the book
+        if config.pause.is_extreme(book.best_bid, book.best_ask) {
+            return StrategyAction::Quote(Vec::new());
+        }
+
+        let params = &config.avellaneda;
+        let order_size = config.order_size;
+        let tick = s.tick_size(&book.yes_token);
+
+        let held = |token: &str| s.inventory.get(token).copied().unwrap_or(0.0);
+        let q = (held(&book.yes_token) - held(&book.no_token)) / order_size;
+        let tau = time_remaining_days(
+            wind_down::cutoff(s, &book.yes_token),
+            params.max_horizon_days,
+        );
+        let estimator = s.volatility.get(&book.yes_token);
+        let sigma = estimator
+            .and_then(|v| v.sigma(&config.volatility))
+            .map(|per_minute| per_minute * MINUTES_PER_DAY.sqrt())
+            .unwrap_or(params.volatility);
+        let kappa = estimator
+            .and_then(|v| v.kappa(&config.volatility))
+            .unwrap_or(params.kappa);
+        let variance_term = params.gamma * sigma.powi(2) * tau;
+
+        let mid = book.mid();
+        let reservation = mid - q * variance_term;
+        let spread = variance_term + (2.0 / params.gamma) * (1.0 + params.gamma / kappa).ln();
+        let phase = wind_down::phase(s, &book.yes_token);
+        let half_spread = (spread / 2.0 * phase.spread_factor).max(tick);
+        // Toxic fills on a side widen its half of the spread
+        let bid_guard = markout::guard(s, &book.yes_token, Side::Buy);
+        let ask_guard = markout::guard(s, &book.yes_token, Side::Sell);
+
+        // Snap outward onto the tick grid and never cross the book
+        let ticks_per_unit = (1.0 / tick).round();
+        let bid = ((reservation - half_spread * bid_guard.widen()) * ticks_per_unit).floor()
+            / ticks_per_unit;
+        let ask = ((reservation + half_spread * ask_guard.widen()) * ticks_per_unit).ceil()
+            / ticks_per_unit;
+        let reward_band = rewards::band(book, s);
+        let (bid, ask) = match &reward_band {
+            Some(band) => band.fit(bid, ask),
+            None => (bid, ask),
+        };
+        let bid = bid.min(book.best_ask - tick).max(tick);
+        let ask = ask.max(book.best_bid + tick).min(1.0 - tick);
+        if bid >= ask {
+            return StrategyAction::Keep;
+        }
+
+        if self.last_quotes != Some((bid, ask)) {
+            self.last_quotes = Some((bid, ask));
+            logger::logln(format!(
+                "Strategy: A-S quotes bid={:.4} ask={:.4} (mid={:.4} r={:.4} q={:+.2} sigma={:.4} kappa={:.1} tau={:.2}d spread={:.4})",
+                bid, ask, mid, reservation, q, sigma, kappa, tau, spread
+            ));
+        }
+        let size = reward_band
+            .as_ref()
+            .map_or(order_size, |band| order_size.max(band.min_size))
+            * phase.size_factor;
+        let mut orders = yes_ladder(book, bid, ask, size, &config.ladder, s);
+        orders.retain(|order| match yes_side(book, order) {
+            Side::Buy => !bid_guard.pulls(),
+            Side::Sell => !ask_guard.pulls(),
+        });
+        phase.filter(&mut orders);
+        StrategyAction::Quote(orders)
+    }
+}
+
+/// Days until `end`, capped at `max_days`; the cap also applies when no date is known
+fn time_remaining_days(end: Option<DateTime<Utc>>, max_days: f64) -> f64 {
+    match end {
+        Some(end) => {
+            let secs = (end - Utc::now()).num_seconds().max(0) as f64;
+            (secs / SECS_PER_DAY).min(max_days)
+        }
+        None => max_days,
+    }
+}
+
+impl Strategy for AvellanedaStoikov {
+    fn name(&self) -> &'static str {
+        STRATEGY_AVELLANEDA
+    }
+
+    fn on_market_update(&mut self, update: &MarketUpdate, s: &mut AppState) -> StrategyAction {
+        let Some(book) = YesBook::from_update(update, s) else {
+            return StrategyAction::Keep;
+        };
+        if pause::is_paused(s, &book.yes_token) || s.shutting_down {
+            return StrategyAction::Keep;
+        }
+        let action = self.quote(&book, s);
+        self.last_book = Some(book);
+        action
+    }
+
+    /// Re-price as time passes and after fills change inventory
+    fn on_timer(&mut self, s: &mut AppState) -> StrategyAction {
+        match self.last_book.clone() {
+            Some(book) if !pause::is_paused(s, &book.yes_token) && !s.shutting_down => {
+                self.quote(&book, s)
+            }
+            _ => StrategyAction::Keep,
+        }
+    }
+}
@@ -0,0 +1,75 @@
+use crate::modules::pause::{self, PauseReason};
+use crate::modules::types::{AppState, MarketUpdate};
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+// -------------------- Circuit Breaker Defaults --------------------
+const DEFAULT_MAX_MOVE_CENTS: f64 = 5.0;
+const DEFAULT_WINDOW_SECS: u64 = 10;
+const DEFAULT_COOLDOWN_SECS: u64 = 30;
+
+/// `circuit_breaker` in the config
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CircuitBreakerConfig {
+    pub enabled: bool,
+    pub max_move_cents: f64, // largest mid move tolerated within the window
+    pub window_secs: u64,    // how far back the reference prices reach
+    pub cooldown_secs: u64,  // quoting stays off this long after the last jump
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_move_cents: DEFAULT_MAX_MOVE_CENTS,
+            window_secs: DEFAULT_WINDOW_SECS,
+            cooldown_secs: DEFAULT_COOLDOWN_SECS,
+        }
+    }
+}
+
+/// Compare the update's mid to every mid of the same token seen within `window_secs`. A move
+/// of more than `max_move_cents` pauses the market (`PriceJump`) and returns true so the
+/// caller cancels its quotes; the new mid becomes the only reference, so a further jump
+/// re-trips and restarts the cool-down.
+pub fn observe(s: &mut AppState, yes_token: &str, update: &MarketUpdate) -> bool {
+    let config = s.market_config(yes_token).circuit_breaker.clone();
+    if !config.enabled || update.best_bid <= 0.0 || update.best_ask <= 0.0 {
+        return false;
+    }
+    let mid = (update.best_bid + update.best_ask) / 2.0;
+    let now = Instant::now();
+    let window = Duration::from_secs(config.window_secs);
+    let refs = s.reference_mids.entry(update.asset_id.clone()).or_default();
+    while refs
+        .front()
+        .is_some_and(|(at, _)| now.duration_since(*at) > window)
+    {
+        refs.pop_front();
+    }
+    let jump = refs
+        .iter()
+        .map(|(_, reference)| mid - reference)
+        .max_by(|a, b| a.abs().total_cmp(&b.abs()))
+        .unwrap_or(0.0);
+    if jump.abs() * 100.0 <= config.max_move_cents {
+        refs.push_back((now, mid));
+        return false;
+    }
+    refs.clear();
+    refs.push_back((now, mid));
+    pause::pause(
+        s,
+        PauseReason::PriceJump(yes_token.to_string()),
+        config.cooldown_secs,
+        &format!(
+            "{} mid moved {:+.1}c to {:.4} within {}s; canceling the market's orders",
+            update.asset_id,
+            jump * 100.0,
+            mid,
+            config.window_secs
+        ),
+    );
+    true
+}
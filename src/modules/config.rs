@@ -0,0 +1,589 @@
+use crate::modules::auto_merge::MergeConfig;
+use crate::modules::avellaneda::AvellanedaConfig;
+use crate::modules::circuit_breaker::CircuitBreakerConfig;
+use crate::modules::ladder::{LadderConfig, MAX_LEVELS};
+use crate::modules::limits::{LimitSet, PortfolioLimits};
+use crate::modules::logger;
+use crate::modules::market_manager::MarketSpec;
+use crate::modules::markout::{MarkoutConfig, HORIZONS_SECS};
+use crate::modules::pause::PauseConfig;
+use crate::modules::provision::ProvisionConfig;
+use crate::modules::queue::QueueConfig;
+use crate::modules::requote::RequoteConfig;
+use crate::modules::rewards::RewardsConfig;
+use crate::modules::risk::RiskLimits;
+use crate::modules::skew::SkewConfig;
+use crate::modules::strategy::{build_strategy, STRATEGY_EDGE};
+use crate::modules::types::AppState;
+use crate::modules::unwind::UnwindConfig;
+use crate::modules::volatility::VolatilityConfig;
+use crate::modules::wind_down::WindDownConfig;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::SystemTime;
+use tokio::sync::Mutex;
+use tokio::time::Duration;
+
+// -------------------- Config Defaults --------------------
+pub const DEFAULT_CONFIG_PATH: &str = "config.toml";
+const RELOAD_POLL_SECS: u64 = 2; // How often the file's mtime is checked
+const DEFAULT_ORDER_SIZE: f64 = 500.0; // The base size of orders to place (e.g., $10).
+const DEFAULT_EDGE_PCT: f64 = 0.02; // Quote 2% away from current best bid/ask to avoid immediate fills on wide spreads.
+const DEFAULT_MAX_INVENTORY_IMBALANCE: f64 = 25.0;
+const DEFAULT_TICK_SIZE: f64 = 0.01; // Used until the market WS reports a tick_size_change
+
+/// Everything tunable about quoting one market. Missing fields take the built-in defaults.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MarketConfig {
+    pub enabled: bool,                // false pulls our quotes and stops quoting
+    pub condition_id: Option<String>, // with no_token, runs this [markets.<yes_token>] section
+    pub no_token: Option<String>,
+    pub event_group: Option<String>, // groups markets under shared limits; overrides the neg-risk event
+    pub strategy: String,
+    pub order_size: f64,                   // shares per quote
+    pub edge_pct: f64,                     // distance from best bid/ask, as a fraction of price
+    pub max_inventory_imbalance: f64,      // dollars between YES and NO; full skew at this level
+    pub max_position_size: Option<f64>, // dollars of unpaired YES/NO; unset = one full ladder at max_price
+    pub end_date: Option<DateTime<Utc>>, // market resolution time, RFC 3339 string; overrides metadata
+    pub game_start: Option<DateTime<Utc>>, // game start time, RFC 3339 string; overrides metadata
+    pub ladder: LadderConfig,
+    pub requote: RequoteConfig,
+    pub queue: QueueConfig,
+    pub skew: SkewConfig,
+    pub avellaneda: AvellanedaConfig,
+    pub volatility: VolatilityConfig,
+    pub pause: PauseConfig,
+    pub circuit_breaker: CircuitBreakerConfig,
+    pub markout: MarkoutConfig,
+    pub wind_down: WindDownConfig,
+    pub rewards: RewardsConfig,
+    pub merge: MergeConfig,
+    pub provision: ProvisionConfig,
+    pub unwind: UnwindConfig,
+    pub tick: TickConfig,
+    pub risk: RiskLimits,
+    pub limits: LimitSet,
+}
+
+impl Default for MarketConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            condition_id: None,
+            no_token: None,
+            event_group: None,
+            strategy: STRATEGY_EDGE.to_string(),
+            order_size: DEFAULT_ORDER_SIZE,
+            edge_pct: DEFAULT_EDGE_PCT,
+            max_inventory_imbalance: DEFAULT_MAX_INVENTORY_IMBALANCE,
+            max_position_size: None,
+            end_date: None,
+            game_start: None,
+            ladder: LadderConfig::default(),
+            requote: RequoteConfig::default(),
+            queue: QueueConfig::default(),
+            skew: SkewConfig::default(),
+            avellaneda: AvellanedaConfig::default(),
+            volatility: VolatilityConfig::default(),
+            pause: PauseConfig::default(),
+            circuit_breaker: CircuitBreakerConfig::default(),
+            markout: MarkoutConfig::default(),
+            wind_down: WindDownConfig::default(),
+            rewards: RewardsConfig::default(),
+            merge: MergeConfig::default(),
+            provision: ProvisionConfig::default(),
+            unwind: UnwindConfig::default(),
+            tick: TickConfig::default(),
+            risk: RiskLimits::default(),
+            limits: LimitSet::default(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TickConfig {
+    pub default_size: f64,     // tick used before (or instead of) the exchange's
+    pub follow_exchange: bool, // apply tick_size_change events from the market WS
+}
+
+impl Default for TickConfig {
+    fn default() -> Self {
+        Self {
+            default_size: DEFAULT_TICK_SIZE,
+            follow_exchange: true,
+        }
+    }
+}
+
+/// `[default]` applies to every market; `[markets.<yes_token_id>]` overrides it field by field.
+/// `[portfolio.group]` limits each event group and `[portfolio.account]` all markets together.
+#[derive(Clone, Debug, Default)]
+pub struct BotConfig {
+    pub default: MarketConfig,
+    pub markets: HashMap<String, MarketConfig>, // yes_token -> config
+    pub portfolio: PortfolioLimits,
+}
+
+/// File layout before `[default]` is merged into each market
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawConfig {
+    #[serde(default)]
+    default: toml::Table,
+    #[serde(default)]
+    markets: HashMap<String, toml::Table>,
+    #[serde(default)]
+    portfolio: toml::Table,
+}
+
+impl BotConfig {
+    /// Parse and validate a TOML document
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut raw: RawConfig = toml::from_str(text).map_err(|e| e.to_string())?;
+        migrate_aggressive_skew("default", &mut raw.default);
+        for (token, overrides) in raw.markets.iter_mut() {
+            migrate_aggressive_skew(&format!("markets.{}", token), overrides);
+        }
+        migrate_portfolio(&mut raw.portfolio);
+        let portfolio: PortfolioLimits = toml::Value::Table(raw.portfolio)
+            .try_into()
+            .map_err(|e| format!("[portfolio]: {}", e))?;
+        let default: MarketConfig = toml::Value::Table(raw.default.clone())
+            .try_into()
+            .map_err(|e| format!("[default]: {}", e))?;
+        let mut markets = HashMap::new();
+        for (token, overrides) in raw.markets {
+            let mut table = raw.default.clone();
+            merge_tables(&mut table, overrides);
+            let market: MarketConfig = toml::Value::Table(table)
+                .try_into()
+                .map_err(|e| format!("[markets.{}]: {}", token, e))?;
+            markets.insert(token, market);
+        }
+        let config = Self {
+            default,
+            markets,
+            portfolio,
+        };
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Config for the market that `asset_id` (YES or NO token) belongs to
+    pub fn market<'a>(&'a self, asset_id: &str, complement: Option<&String>) -> &'a MarketConfig {
+        self.markets
+            .get(asset_id)
+            .or_else(|| complement.and_then(|c| self.markets.get(c)))
+            .unwrap_or(&self.default)
+    }
+
+    /// Markets to run: `[markets.*]` sections that name both their condition and NO token
+    pub fn market_specs(&self) -> Vec<MarketSpec> {
+        let mut specs: Vec<MarketSpec> = self
+            .markets
+            .iter()
+            .filter_map(|(yes_token, market)| {
+                Some(MarketSpec {
+                    condition_id: market.condition_id.clone()?,
+                    yes_token: yes_token.clone(),
+                    no_token: market.no_token.clone()?,
+                })
+            })
+            .collect();
+        specs.sort_by(|a, b| a.yes_token.cmp(&b.yes_token));
+        specs
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        let mut errors = Vec::new();
+        self.default.validate("default", &mut errors);
+        if self.default.condition_id.is_some() || self.default.no_token.is_some() {
+            errors.push("[default] condition_id and no_token belong in [markets.*]".to_string());
+        }
+        for (token, market) in &self.markets {
+            let section = format!("markets.{}", token);
+            market.validate(&section, &mut errors);
+            if market.condition_id.is_some() != market.no_token.is_some() {
+                errors.push(format!(
+                    "[{}] condition_id and no_token must be set together",
+                    section
+                ));
+            }
+        }
+        for (section, limits) in [
+            ("portfolio.group", &self.portfolio.group),
+            ("portfolio.account", &self.portfolio.account),
+        ] {
+            if !limits.is_valid() {
+                errors.push(format!("[{}] limits must be >= 0", section));
+            }
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors.join("; "))
+        }
+    }
+}
+
+impl MarketConfig {
+    fn validate(&self, section: &str, errors: &mut Vec<String>) {
+        let mut check = |ok: bool, what: &str| {
+            if !ok {
+                errors.push(format!("[{}] {}", section, what));
+            }
+        };
+        check(
+            build_strategy(&self.strategy).is_some(),
+            &format!("unknown strategy '{}'", self.strategy),
+        );
+        check(self.order_size > 0.0, "order_size must be > 0");
+        check(
+            (0.0..1.0).contains(&self.edge_pct),
+            "edge_pct must be in [0, 1)",
+        );
+        check(
+            (1..=MAX_LEVELS).contains(&self.ladder.levels),
+            &format!("ladder.levels must be in 1..={}", MAX_LEVELS),
+        );
+        check(
+            self.ladder.spacing_ticks >= 1,
+            "ladder.spacing_ticks must be >= 1",
+        );
+        check(
+            self.ladder.size_growth > 0.0,
+            "ladder.size_growth must be > 0",
+        );
+        let q = &self.requote;
+        check(
+            q.min_lifetime_secs >= 0.0 && q.min_move_ticks >= 1,
+            "requote needs min_lifetime_secs >= 0 and min_move_ticks >= 1",
+        );
+        check(
+            q.max_age_secs == 0 || q.max_age_secs as f64 > q.min_lifetime_secs,
+            "requote.max_age_secs must be 0 or > min_lifetime_secs",
+        );
+        check(
+            (0.0..=1.0).contains(&self.queue.max_ahead_fraction),
+            "queue.max_ahead_fraction must be in [0, 1]",
+        );
+        check(
+            (0.0..0.5).contains(&self.skew.max_shift),
+            "skew.max_shift must be in [0, 0.5)",
+        );
+        check(self.skew.steepness >= 0.0, "skew.steepness must be >= 0");
+        let a = &self.avellaneda;
+        check(a.gamma > 0.0, "avellaneda.gamma must be > 0");
+        check(a.kappa > 0.0, "avellaneda.kappa must be > 0");
+        check(a.volatility >= 0.0, "avellaneda.volatility must be >= 0");
+        check(
+            a.max_horizon_days > 0.0,
+            "avellaneda.max_horizon_days must be > 0",
+        );
+        check(
+            self.max_inventory_imbalance >= 0.0,
+            "max_inventory_imbalance must be >= 0",
+        );
+        check(
+            self.max_position_size.is_none_or(|max| max >= 0.0),
+            "max_position_size must be >= 0",
+        );
+        check(
+            self.tick.default_size > 0.0 && self.tick.default_size < 1.0,
+            "tick.default_size must be in (0, 1)",
+        );
+        let v = &self.volatility;
+        check(
+            v.fast_half_life_secs > 0.0 && v.slow_half_life_secs >= v.fast_half_life_secs,
+            "volatility needs 0 < fast_half_life_secs <= slow_half_life_secs",
+        );
+        check(v.baseline > 0.0, "volatility.baseline must be > 0");
+        check(
+            0.0 < v.min_multiplier && v.min_multiplier <= 1.0 && v.max_multiplier >= 1.0,
+            "volatility needs 0 < min_multiplier <= 1 <= max_multiplier",
+        );
+        let p = &self.pause;
+        check(
+            0.0 < p.extreme_low
+                && p.extreme_low < p.resume_low
+                && p.resume_low < p.resume_high
+                && p.resume_high < p.extreme_high
+                && p.extreme_high < 1.0,
+            "pause needs 0 < extreme_low < resume_low < resume_high < extreme_high < 1",
+        );
+        let cb = &self.circuit_breaker;
+        check(
+            cb.max_move_cents > 0.0 && cb.window_secs >= 1,
+            "circuit_breaker needs max_move_cents > 0 and window_secs >= 1",
+        );
+        let m = &self.markout;
+        check(
+            HORIZONS_SECS.contains(&m.horizon_secs),
+            &format!("markout.horizon_secs must be one of {:?}", HORIZONS_SECS),
+        );
+        check(m.threshold >= 0.0, "markout.threshold must be >= 0");
+        check(
+            m.widen_multiplier >= 1.0,
+            "markout.widen_multiplier must be >= 1",
+        );
+        let w = &self.wind_down;
+        check(
+            0.0 <= w.pull_mins
+                && w.pull_mins <= w.reduce_only_mins
+                && w.reduce_only_mins <= w.start_mins,
+            "wind_down needs 0 <= pull_mins <= reduce_only_mins <= start_mins",
+        );
+        check(
+            0.0 < w.min_size_factor && w.min_size_factor <= 1.0,
+            "wind_down.min_size_factor must be in (0, 1]",
+        );
+        check(
+            w.max_spread_factor >= 1.0,
+            "wind_down.max_spread_factor must be >= 1",
+        );
+        check(
+            self.rewards.band_fraction > 0.0 && self.rewards.band_fraction <= 1.0,
+            "rewards.band_fraction must be in (0, 1]",
+        );
+        let g = &self.merge;
+        check(
+            g.min_pairs >= 1.0 && g.keep_pairs >= 0.0,
+            "merge needs min_pairs >= 1 and keep_pairs >= 0",
+        );
+        check(g.interval_secs >= 1, "merge.interval_secs must be >= 1");
+        let pv = &self.provision;
+        check(
+            pv.target_shares.is_none_or(|t| t >= 0.0),
+            "provision.target_shares must be >= 0",
+        );
+        check(
+            pv.min_split >= 1.0 && pv.interval_secs >= 1,
+            "provision needs min_split >= 1 and interval_secs >= 1",
+        );
+        let u = &self.unwind;
+        check(
+            u.deadline_secs >= u.passive_secs && u.max_slippage_cents >= 0.0,
+            "unwind needs deadline_secs >= passive_secs and max_slippage_cents >= 0",
+        );
+        let r = &self.risk;
+        check(
+            0.0 < r.min_price && r.min_price < r.max_price && r.max_price < 1.0,
+            "risk needs 0 < min_price < max_price < 1",
+        );
+        check(
+            r.max_order_notional > 0.0,
+            "risk.max_order_notional must be > 0",
+        );
+        check(
+            r.max_open_notional_per_asset >= r.max_order_notional,
+            "risk.max_open_notional_per_asset must be >= max_order_notional",
+        );
+        check(r.min_order_size >= 0.0, "risk.min_order_size must be >= 0");
+        // The ladder's largest level must pass the order notional check at any allowed price
+        let largest_level = self.order_size
+            * self
+                .ladder
+                .size_growth
+                .powi(self.ladder.levels.saturating_sub(1) as i32)
+                .max(1.0);
+        check(
+            largest_level * r.max_price <= r.max_order_notional,
+            "order_size * ladder.size_growth^(levels - 1) * risk.max_price must be <= risk.max_order_notional",
+        );
+        check(self.limits.is_valid(), "limits must be >= 0");
+    }
+}
+
+/// Overlay `overlay` onto `base`, recursing into nested tables
+fn merge_tables(base: &mut toml::Table, overlay: toml::Table) {
+    for (key, value) in overlay {
+        match value {
+            toml::Value::Table(nested) if matches!(base.get(&key), Some(toml::Value::Table(_))) => {
+                if let Some(toml::Value::Table(inner)) = base.get_mut(&key) {
+                    merge_tables(inner, nested);
+                }
+            }
+            value => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+// -------------------- Deprecated Keys --------------------
+/// `aggressive_skew` gave way to the skew curve; an old value becomes `skew.max_shift`
+/// unless the section sets that too, in which case it is ignored
+fn migrate_aggressive_skew(section: &str, table: &mut toml::Table) {
+    let Some(value) = table.remove("aggressive_skew") else {
+        return;
+    };
+    let skew = table
+        .entry("skew")
+        .or_insert_with(|| toml::Value::Table(toml::Table::new()));
+    match skew {
+        toml::Value::Table(skew) if !skew.contains_key("max_shift") => {
+            skew.insert("max_shift".to_string(), value);
+            logger::logln(format!(
+                "Config: [{}] aggressive_skew is deprecated; using it as skew.max_shift",
+                section
+            ));
+        }
+        _ => logger::logln(format!(
+            "Config: [{}] aggressive_skew is deprecated and ignored; skew.max_shift is set",
+            section
+        )),
+    }
+}
+
+/// `[portfolio] max_net_exposure` and `max_open_notional` became the account layer's
+/// `max_notional` and `max_open_notional`; an old key is moved there unless the account sets it.
+/// An account layer created for them starts from the defaults, as the old section did.
+fn migrate_portfolio(table: &mut toml::Table) {
+    let explicit: Vec<String> = match table.get("account") {
+        Some(toml::Value::Table(account)) => account.keys().cloned().collect(),
+        _ => Vec::new(),
+    };
+    for (old, new) in [
+        ("max_net_exposure", "max_notional"),
+        ("max_open_notional", "max_open_notional"),
+    ] {
+        let Some(value) = table.remove(old) else {
+            continue;
+        };
+        if explicit.iter().any(|key| key == new) {
+            logger::logln(format!(
+                "Config: [portfolio] {} is deprecated and ignored; [portfolio.account] {} is set",
+                old, new
+            ));
+            continue;
+        }
+        let account = table.entry("account").or_insert_with(|| {
+            toml::Value::Table(
+                toml::Table::try_from(PortfolioLimits::default().account).unwrap_or_default(),
+            )
+        });
+        if let toml::Value::Table(account) = account {
+            account.insert(new.to_string(), value);
+        }
+        logger::logln(format!(
+            "Config: [portfolio] {} is deprecated; using it as [portfolio.account] {}",
+            old, new
+        ));
+    }
+}
+
+// -------------------- Loading --------------------
+/// Load the config at startup. A missing file means built-in defaults; an invalid one is fatal.
+pub fn load_config(path: &Path) -> BotConfig {
+    match fs::read_to_string(path) {
+        Ok(text) => BotConfig::parse(&text)
+            .unwrap_or_else(|e| panic!("Invalid config {}: {}", path.display(), e)),
+        Err(_) => {
+            println!(
+                "Config: {} not found, using built-in defaults",
+                path.display()
+            );
+            BotConfig::default()
+        }
+    }
+}
+
+fn modified_at(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+// -------------------- Hot Reload Task --------------------
+/// Poll the config file and swap `AppState.config` whenever it changes. Tasks read the
+/// config through the shared state, so nothing is restarted; an invalid edit is logged and
+/// the previous config stays in force.
+pub async fn config_watch_task(path: PathBuf, state: Arc<Mutex<AppState>>) {
+    let mut last_modified = modified_at(&path);
+    let mut interval = tokio::time::interval(Duration::from_secs(RELOAD_POLL_SECS));
+    loop {
+        interval.tick().await;
+        if state.lock().await.shutting_down {
+            break;
+        }
+
+        let modified = modified_at(&path);
+        if modified.is_none() || modified == last_modified {
+            continue;
+        }
+        last_modified = modified;
+
+        let parsed = fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|text| BotConfig::parse(&text));
+        match parsed {
+            Ok(config) => {
+                state.lock().await.config = config;
+                logger::logln(format!("Config: Reloaded {}", path.display()));
+            }
+            Err(e) => {
+                logger::logln(format!(
+                    "Config: Ignoring invalid {}: {}. Keeping previous config.",
+                    path.display(),
+                    e
+                ));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::risk;
+    use crate::modules::strategy::StrategyAction;
+    use crate::modules::test_support::add_market;
+    use crate::modules::types::{MarketUpdate, Order};
+
+    fn example() -> BotConfig {
+        BotConfig::parse(include_str!("../../config.example.toml")).unwrap()
+    }
+
+    /// Quotes the example config's strategy wants for a fresh market at 0.49/0.51, holding
+    /// `held` of each token
+    fn example_quotes(held: f64) -> (AppState, Vec<Order>) {
+        let mut s = AppState {
+            config: example(),
+            ..AppState::default()
+        };
+        let no_token = add_market(&mut s, "yes", 0.49, 0.51);
+        s.inventory.insert("yes".to_string(), held);
+        s.inventory.insert(no_token, held);
+        let update = MarketUpdate {
+            asset_id: "yes".to_string(),
+            best_bid: 0.49,
+            best_ask: 0.51,
+            ts: 0,
+        };
+        let mut strategy = build_strategy(&s.config.default.strategy).unwrap();
+        match strategy.on_market_update(&update, &mut s) {
+            StrategyAction::Quote(orders) => (s, orders),
+            other => panic!("expected quotes, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn example_config_quotes_pass_the_risk_gate() {
+        let config = example().default;
+        let provisioned = config.ladder.side_shares(config.order_size);
+        // Provisioned, both sides sell inventory; unfunded, both sides buy
+        for held in [provisioned, 0.0] {
+            let (s, orders) = example_quotes(held);
+            assert_eq!(orders.len(), 2, "held {}", held);
+            for order in &orders {
+                if let Err(rejection) = risk::check_order(order, &s) {
+                    panic!("held {}: {:?} rejected: {}", held, order, rejection);
+                }
+            }
+        }
+    }
+}
@@ -1,25 +1,35 @@
+use chrono::{DateTime, Utc};
 use polymarket_rs_client::ClobClient;
 use serde_json;
 use std::{collections::HashMap, env, sync::Arc};
 use tokio::sync::Mutex;
 
-use crate::modules::types::PositionApiResponse;
+use crate::modules::rewards::RewardParams;
+use crate::modules::types::{GammaMarketResponse, PositionApiResponse};
+use crate::modules::wind_down::MarketSchedule;
+
+/// What the bot reads from a market's Gamma metadata
+#[derive(Debug)]
+pub struct MarketMetadata {
+    pub schedule: MarketSchedule,
+    pub rewards: Option<RewardParams>, // None when the market pays no liquidity rewards
+    pub event_group: Option<String>,   // neg-risk event id; None for standalone markets
+}
 
 /// Fetch current positions from Polymarket API using the /positions endpoint
 /// Returns a map of token_id -> quantity for the specified tokens
 pub async fn fetch_current_positions(
     client: &Arc<Mutex<ClobClient>>,
-    assets_id: &String,
+    token_ids: &[String],
     condition_id: &String,
 ) -> Result<HashMap<String, f64>, Box<dyn std::error::Error>> {
     let _ = client; // not used currently; kept for parity with signature
     let mut positions = HashMap::new();
 
     // Initialize with zero positions
-    // for token_id in token_ids {
-    // positions.insert(token_id.clone(), 0.0);
-    // }
-    positions.insert(assets_id.clone(), 0.0);
+    for token_id in token_ids {
+        positions.insert(token_id.clone(), 0.0);
+    }
 
     // Prefer address from client; fallback to env PROXYWALLET if provided
     let user_address = env::var("PROXYWALLET")
@@ -42,7 +52,7 @@ pub async fn fetch_current_positions(
             if response.status().is_success() {
                 let positions_data: Vec<PositionApiResponse> = response.json().await?;
                 for position in positions_data {
-                    if *assets_id == position.asset {
+                    if token_ids.contains(&position.asset) {
                         positions.insert(position.asset, position.size);
                     }
                 }
@@ -56,6 +66,59 @@ pub async fn fetch_current_positions(
     Ok(positions)
 }
 
+/// Fetch the market's end date, game start time and liquidity reward terms from the Gamma API
+pub async fn fetch_market_metadata(
+    condition_id: &str,
+) -> Result<MarketMetadata, Box<dyn std::error::Error>> {
+    let markets_url = "https://gamma-api.polymarket.com/markets";
+    let http_client = reqwest::Client::new();
+    let markets: Vec<GammaMarketResponse> = http_client
+        .get(markets_url)
+        .query(&[("condition_ids", condition_id)])
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    let market = markets
+        .into_iter()
+        .find(|m| m.condition_id.eq_ignore_ascii_case(condition_id))
+        .ok_or("market not found")?;
+    let daily_rate: f64 = market
+        .clob_rewards
+        .iter()
+        .map(|r| r.rewards_daily_rate)
+        .sum();
+    let rewards = match (market.rewards_min_size, market.rewards_max_spread) {
+        (Some(min_size), Some(max_spread_cents)) if max_spread_cents > 0.0 => Some(RewardParams {
+            min_size,
+            max_spread: max_spread_cents / 100.0,
+            daily_rate,
+        }),
+        _ => None,
+    };
+    Ok(MarketMetadata {
+        schedule: MarketSchedule {
+            end_date: market.end_date.as_deref().and_then(parse_gamma_time),
+            game_start: market.game_start_time.as_deref().and_then(parse_gamma_time),
+        },
+        rewards,
+        event_group: market
+            .neg_risk
+            .unwrap_or(false)
+            .then_some(market.neg_risk_market_id)
+            .flatten(),
+    })
+}
+
+/// Gamma mixes RFC 3339 and "YYYY-MM-DD HH:MM:SS+00"
+fn parse_gamma_time(text: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(text)
+        .or_else(|_| DateTime::parse_from_str(text, "%Y-%m-%d %H:%M:%S%#z"))
+        .ok()
+        .map(|t| t.with_timezone(&Utc))
+}
+
 /// Fetch open orders from Polymarket API
 /// Returns a list of open orders for debugging purposes
 pub async fn fetch_open_orders(
@@ -0,0 +1,256 @@
+use crate::modules::ladder::yes_ladder;
+use crate::modules::logger;
+use crate::modules::markout::{self, MarkoutGuard};
+use crate::modules::pause::{self, PauseReason};
+use crate::modules::rewards;
+use crate::modules::skew::dollar_imbalance;
+use crate::modules::strategy::{
+    yes_side, Fill, OrderEvent, Strategy, StrategyAction, YesBook, STRATEGY_EDGE,
+};
+use crate::modules::types::{AppState, MarketUpdate, Side, MSG_CANCELLATION};
+use crate::modules::wind_down::{self, WindDown};
+use std::collections::HashMap;
+
+// -------------------- Edge Market Maker Config --------------------
+// Size, edge and skew come from the market's config on every update, so reloads apply immediately
+const VOL_STEPS_PER_UNIT: f64 = 4.0; // requote when the spread multiplier moves by 0.25
+
+// -------------------- Edge Market Maker --------------------
+/// The original quoting rules: quotes anchored `edge_pct` (scaled by realized volatility)
+/// outside the best bid/ask, shifted by the inventory skew curve, replaced only when the
+/// 1-cent mid bucket or any other quote input moves, and paused on extreme prices. A side
+/// whose recent fills mark out badly is widened or pulled per `[markout]`.
+pub struct EdgeMarketMaker {
+    last_inputs: HashMap<String, QuoteInputs>, // yes_token -> inputs of the resting quotes
+    last_book: Option<YesBook>,                // re-quoted from on a timer when the book is quiet
+}
+
+impl Default for EdgeMarketMaker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Everything besides the mid bucket that changes our quotes
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct QuoteInputs {
+    skew_ticks: i64,
+    vol_step: i64,
+    bid_guard: MarkoutGuard,
+    ask_guard: MarkoutGuard,
+    wind_down: WindDown,
+}
+
+impl EdgeMarketMaker {
+    pub fn new() -> Self {
+        Self {
+            last_inputs: HashMap::new(),
+            last_book: None,
+        }
+    }
+
+    /// Quote `book` unless neither its mid bucket nor any other quote input moved
+    fn quote(&mut self, book: &YesBook, s: &mut AppState) -> StrategyAction {
+        let open_orders_size = s
+            .my_open_orders
+            .values()
+            .filter(|order| order.asset_id == book.yes_token || order.asset_id == book.no_token)
+            .count();
+
+        // Risk Check 1: Pause if prices are too extreme; the trading task resumes us once
+        // they are back inside the resume band for the cool-down.
+        let pause_config = s.market_config(&book.yes_token).pause.clone();
+        if pause_config.is_extreme(book.best_bid, book.best_ask) {
+            pause::pause(
+                s,
+                PauseReason::ExtremePrice(book.yes_token.clone()),
+                pause_config.extreme_cooldown_secs,
+                &format!(
+                    "YES bid={:.4} ask={:.4}; canceling the market's orders",
+                    book.best_bid, book.best_ask
+                ),
+            );
+            return StrategyAction::CancelAll;
+        }
+
+        // -------------------- 2. Conditional requote on mid-price bucket or skew --------------------
+        // Compute mid and a coarse bucket; only re-quote if the bucket or the skew (in ticks) changed.
+        // The mid always comes from the YES book (the NO book only until YES is seen), so updates
+        // of the two books do not flip the bucket back and forth.
+        let current_mid_price = markout::yes_mid(s, &book.yes_token).unwrap_or_else(|| book.mid());
+        let mid_bucket = (current_mid_price * 100.0).ceil() as i32; // 1-cent buckets
+        let prev = s.last_mid_bucket.insert(book.yes_token.clone(), mid_bucket);
+        let should_requote_price = match prev {
+            Some(last) => last != mid_bucket,
+            None => true,
+        };
+
+        let config = s.market_config(&book.yes_token);
+        let (edge_pct, order_size) = (config.edge_pct, config.order_size);
+        let imbalance_dollars = dollar_imbalance(book, s);
+        let skew = config
+            .skew
+            .shift(imbalance_dollars, config.max_inventory_imbalance);
+        // Fast markets widen the edge, quiet ones tighten it
+        let vol_multiplier = s
+            .volatility
+            .get(&book.yes_token)
+            .map(|v| v.spread_multiplier(&config.volatility))
+            .unwrap_or(1.0);
+        let edge_pct = edge_pct * vol_multiplier;
+        let tick = s.tick_size(&book.yes_token);
+        let inputs = QuoteInputs {
+            skew_ticks: (skew / tick).round() as i64,
+            vol_step: (vol_multiplier * VOL_STEPS_PER_UNIT).round() as i64,
+            bid_guard: markout::guard(s, &book.yes_token, Side::Buy),
+            ask_guard: markout::guard(s, &book.yes_token, Side::Sell),
+            wind_down: wind_down::phase(s, &book.yes_token),
+        };
+        let prev_inputs = self.last_inputs.insert(book.yes_token.clone(), inputs);
+        let should_requote_inputs = prev_inputs != Some(inputs);
+
+        if !should_requote_price && !should_requote_inputs {
+            // No material move; keep existing quotes
+            logger::logln(format!(
+                "Strategy: Price not moved. Returning. Current open orders={} and current mid bucket={}, best bid={:.2}, best ask={:.2} last mid bucket={:?}",
+                open_orders_size, mid_bucket, book.best_bid, book.best_ask, prev
+            ));
+            return StrategyAction::Keep;
+        }
+        logger::logln(format!(
+            "Strategy: {}. Requoting. Current open orders={} and current mid bucket={}",
+            if should_requote_price {
+                "Price moved"
+            } else {
+                "Skew, volatility, markout guard or wind-down changed"
+            },
+            open_orders_size,
+            mid_bucket
+        ));
+
+        // -------------------- 3. Calculate Quotes anchored to best bid/ask with inventory skew --------------------
+        // Long YES shifts both quotes down (sell YES faster, buy less); short YES shifts them up.
+        // Toxic fills on a side widen its edge; the run-up to resolution widens both and
+        // shrinks sizes
+        // In rewards mode sizes are at least the reward minimum before the wind-down shrinks them
+        let reward_band = rewards::band(book, s);
+        let edge_pct = edge_pct * inputs.wind_down.spread_factor;
+        let order_size = reward_band
+            .as_ref()
+            .map_or(order_size, |band| order_size.max(band.min_size))
+            * inputs.wind_down.size_factor;
+        let bid_edge = edge_pct * inputs.bid_guard.widen();
+        let ask_edge = edge_pct * inputs.ask_guard.widen();
+        let our_bid_price = (book.best_bid * (1.0 - bid_edge) + skew).max(tick);
+        let our_ask_price = (book.best_ask + ask_edge * book.best_ask + skew).min(1.0 - tick);
+
+        // Quantize to the tick: round the bid up and the ask down
+        let ticks_per_unit = (1.0 / tick).round();
+        let our_bid_price = ((our_bid_price * ticks_per_unit).ceil() / ticks_per_unit).max(tick);
+        let our_ask_price =
+            ((our_ask_price * ticks_per_unit).floor() / ticks_per_unit).min(1.0 - tick);
+        // Rewards mode pulls quotes outside the scoring band back into it
+        let (our_bid_price, our_ask_price) = match &reward_band {
+            Some(band) => band.fit(our_bid_price, our_ask_price),
+            None => (our_bid_price, our_ask_price),
+        };
+        // Skew must never turn a quote into a taker order
+        let our_bid_price = our_bid_price.min(book.best_ask - tick);
+        let our_ask_price = our_ask_price.max(book.best_bid + tick);
+
+        logger::logln(format!(
+            "Strategy: Calculated quotes: bid={:.2}, ask={:.2} and best bid={:.2}, best ask={:.2} imbalance=${:.2} skew={:+.4} vol x{:.2} markout bid={:?} ask={:?} {:?} open orders={} yes_token={}",
+            our_bid_price, our_ask_price, book.best_bid, book.best_ask, imbalance_dollars, skew, vol_multiplier, inputs.bid_guard, inputs.ask_guard, inputs.wind_down, open_orders_size, book.yes_token
+        ));
+
+        // Ensure our bid is not higher than our ask after all adjustments.
+        if our_bid_price >= our_ask_price {
+            logger::logln(
+                "Strategy: Calculated bid is higher than ask. Skipping quote placement."
+                    .to_string(),
+            );
+            return StrategyAction::Keep;
+        }
+
+        // -------------------- 4. Desired Two-Sided Quotes --------------------
+        // Each level goes on whichever token our inventory lets us fill it with
+        let ladder = &s.market_config(&book.yes_token).ladder;
+        let mut orders = yes_ladder(book, our_bid_price, our_ask_price, order_size, ladder, s);
+        orders.retain(|order| match yes_side(book, order) {
+            Side::Buy => !inputs.bid_guard.pulls(),
+            Side::Sell => !inputs.ask_guard.pulls(),
+        });
+        // Near resolution only inventory we hold is offered, then nothing at all
+        inputs.wind_down.filter(&mut orders);
+        StrategyAction::Quote(orders)
+    }
+}
+
+impl Strategy for EdgeMarketMaker {
+    fn name(&self) -> &'static str {
+        STRATEGY_EDGE
+    }
+
+    fn on_market_update(&mut self, update: &MarketUpdate, s: &mut AppState) -> StrategyAction {
+        // -------------------- 1. Get State & Perform Risk Checks --------------------
+        // Both outcome tokens come from token_pairs; updates for either book are seen in YES prices
+        let Some(book) = YesBook::from_update(update, s) else {
+            return StrategyAction::Keep;
+        };
+        if pause::is_paused(s, &book.yes_token) || s.shutting_down {
+            return StrategyAction::Keep;
+        }
+        self.last_book = Some(book.clone());
+        self.quote(&book, s)
+    }
+
+    /// Wind-down phases and markout guards move with time rather than the book: re-quote a quiet
+    /// market once either differs from what the resting quotes were built with
+    fn on_timer(&mut self, s: &mut AppState) -> StrategyAction {
+        let Some(book) = self.last_book.clone() else {
+            return StrategyAction::Keep;
+        };
+        if pause::is_paused(s, &book.yes_token) || s.shutting_down {
+            return StrategyAction::Keep;
+        }
+        let Some(last) = self.last_inputs.get(&book.yes_token) else {
+            return StrategyAction::Keep;
+        };
+        let changed = last.wind_down != wind_down::phase(s, &book.yes_token)
+            || last.bid_guard != markout::guard(s, &book.yes_token, Side::Buy)
+            || last.ask_guard != markout::guard(s, &book.yes_token, Side::Sell);
+        if !changed {
+            return StrategyAction::Keep;
+        }
+        self.quote(&book, s)
+    }
+
+    /// A filled quote leaves that side empty; forget the bucket so the next update re-quotes
+    fn on_fill(&mut self, fill: &Fill, s: &mut AppState) -> StrategyAction {
+        logger::logln(format!(
+            "Strategy: Fill on {} - {:?} {} @ {:.4} x {:.2}. Re-quoting on next update.",
+            fill.order_id, fill.side, fill.asset_id, fill.price, fill.size
+        ));
+        forget_bucket(s, &fill.asset_id);
+        StrategyAction::Keep
+    }
+
+    /// Same for quotes the exchange pulled on its own (expiry, tick change, ...)
+    fn on_order_event(&mut self, event: &OrderEvent, s: &mut AppState) -> StrategyAction {
+        if event.msg_type == MSG_CANCELLATION {
+            logger::logln(format!(
+                "Strategy: Order {} on {} canceled. Re-quoting on next update.",
+                event.order_id, event.asset_id
+            ));
+            forget_bucket(s, &event.asset_id);
+        }
+        StrategyAction::Keep
+    }
+}
+
+/// Drop the mid bucket of the market `asset_id` belongs to
+fn forget_bucket(s: &mut AppState, asset_id: &str) {
+    if let Some(yes_token) = s.yes_token_of(asset_id).map(str::to_string) {
+        s.last_mid_bucket.remove(&yes_token);
+    }
+}
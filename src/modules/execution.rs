@@ -1,109 +1,464 @@
 use crate::modules::logger;
 use crate::modules::{
+    auto_merge, heartbeat, limits,
+    market_manager::MarketSpec,
+    metrics::LatencyStage,
+    queue,
+    rate_limit::{Endpoint, RateLimiter},
+    retry::{classify_error, classify_post_response, classify_send_error, with_retry, ErrorClass},
+    risk,
     split_merge::{self, TransactionType},
-    types::{AppState, BotCommand, Side},
+    types::{now_millis, AppState, BotCommand, CancelState, Order, OrderId, Side, TimedCommand},
 };
-use polymarket_rs_client::{ClobClient, OrderArgs, OrderType, Side as PmSide};
+use polymarket_rs_client::{ClobClient, OpenOrderParams, OrderArgs, OrderType, Side as PmSide};
+use rust_decimal::prelude::ToPrimitive;
 use rust_decimal::Decimal;
-// (no-op)
+use std::future::Future;
 use std::sync::Arc;
+use std::time::Instant;
 use tokio::sync::{mpsc, Mutex};
+use tokio::task::JoinHandle;
+use tokio::time::Duration;
+
+const RECONCILE_INTERVAL_MS: u64 = 2_000; // How often unconfirmed cancels are retried
+const RECONCILE_MAX_BACKOFF_MS: i64 = 30_000;
+const ORDER_STATUS_LIVE: &str = "LIVE";
+const MAX_UNCONFIRMED_CHECKS: u32 = 2; // open-order checks before an unconfirmed post is given up
+const CLOCK_SKEW_SECS: u64 = 5; // slack when matching an order's creation time to our post
+
+/// Failure of a backend call: how to treat it plus a human-readable reason
+pub type ExecResult<T> = Result<T, (ErrorClass, String)>;
+
+/// An accepted order with the timing of its final (successful) attempt
+#[derive(Clone, Debug)]
+pub struct Placement {
+    pub order_id: OrderId,
+    pub sign: Option<Duration>,
+    pub post: Option<Duration>,
+}
+
+// -------------------- Execution Backend --------------------
+/// Where orders actually go. The execution task owns state bookkeeping; a backend only
+/// talks to its venue (the live CLOB or the paper simulator).
+pub trait ExecutionBackend: Send + Sync + 'static {
+    fn name(&self) -> &'static str;
+    /// Whether the user feed sends a PLACEMENT for this backend's orders (used for ack latency)
+    fn reports_placements(&self) -> bool {
+        true
+    }
+    /// Place a resting order and return the venue's order id. With an expiration
+    /// (unix seconds) the order is GTD, otherwise GTC.
+    fn place_order(
+        &self,
+        order: &Order,
+        expiration: Option<u64>,
+    ) -> impl Future<Output = ExecResult<Placement>> + Send;
+    fn cancel(&self, order_id: &str) -> impl Future<Output = ExecResult<()>> + Send;
+    fn cancel_all(&self) -> impl Future<Output = ExecResult<()>> + Send;
+    /// Ask the venue whether an order is still resting (used to reconcile failed cancels)
+    fn order_is_live(&self, order_id: &str) -> impl Future<Output = ExecResult<bool>> + Send;
+    /// Merge `shares` YES/NO pairs of the condition back into USDC
+    fn merge(
+        &self,
+        shares: i64,
+        condition_id: &str,
+        proxy_wallet: &str,
+    ) -> impl Future<Output = ExecResult<()>> + Send;
+    /// Split `shares` USDC into as many YES/NO pairs of the condition
+    fn split(
+        &self,
+        shares: i64,
+        condition_id: &str,
+        proxy_wallet: &str,
+    ) -> impl Future<Output = ExecResult<()>> + Send;
+}
+
+// -------------------- Live CLOB Backend --------------------
+/// Signs and posts to the Polymarket CLOB; every call is rate limited and retried where it is
+/// safe to repeat. The client lock is taken per attempt, never across a backoff.
+pub struct LiveClobBackend {
+    client: Arc<Mutex<ClobClient>>,
+    limiter: RateLimiter,
+}
+
+impl LiveClobBackend {
+    pub fn new(client: Arc<Mutex<ClobClient>>) -> Self {
+        // Every CLOB call goes through the limiter so a burst of requotes queues locally
+        // instead of getting the API key throttled.
+        Self {
+            client,
+            limiter: RateLimiter::polymarket_defaults(),
+        }
+    }
+
+    /// Id of a resting order on the exchange matching `order`, created at or after `since`
+    /// (unix seconds); used to learn whether an unconfirmed post went through
+    async fn find_resting(&self, order: &Order, since: u64) -> ExecResult<Option<OrderId>> {
+        let params = OpenOrderParams {
+            id: None,
+            asset_id: Some(order.asset_id.clone()),
+            market: None,
+        };
+        let open = with_retry(&self.limiter, Endpoint::GetOrder, "get orders", || {
+            let params = &params;
+            async move {
+                self.client
+                    .lock()
+                    .await
+                    .get_orders(Some(params), None)
+                    .await
+                    .map_err(|e| (classify_error(&e), format!("{:?}", e)))
+            }
+        })
+        .await?;
+        let side = match order.side {
+            Side::Buy => PmSide::BUY,
+            Side::Sell => PmSide::SELL,
+        };
+        let same = |value: Decimal, expected: f64| {
+            value
+                .to_f64()
+                .is_some_and(|value| (value - expected).abs() < 1e-9)
+        };
+        Ok(open
+            .into_iter()
+            .find(|o| {
+                o.side == side
+                    && o.created_at >= since
+                    && same(o.price, order.price)
+                    && same(o.original_size, order.size)
+            })
+            .map(|o| o.id))
+    }
+}
+
+impl ExecutionBackend for LiveClobBackend {
+    fn name(&self) -> &'static str {
+        "live"
+    }
+
+    async fn place_order(&self, order: &Order, expiration: Option<u64>) -> ExecResult<Placement> {
+        // Convert our internal Order to Polymarket OrderArgs
+        let pm_side = match order.side {
+            Side::Buy => PmSide::BUY,
+            Side::Sell => PmSide::SELL,
+        };
+
+        let order_args = OrderArgs {
+            token_id: order.asset_id.clone(),
+            price: Decimal::from_f64_retain(order.price).unwrap_or_default(),
+            size: Decimal::from_f64_retain(order.size).unwrap_or_default(),
+            side: pm_side,
+        };
+
+        // Sign and post under the POST /order budget; signing is redone on each
+        // attempt because a signed order is consumed by post_order. A POST is only
+        // repeated when it cannot have been accepted; after a 5xx or timeout the open
+        // orders are checked first.
+        let order_type = if expiration.is_some() {
+            OrderType::GTD
+        } else {
+            OrderType::GTC
+        };
+        let since = ((now_millis() / 1000) as u64).saturating_sub(CLOCK_SKEW_SECS);
+        let mut checks = 0;
+        let (posted, sign, post) = loop {
+            let result = with_retry(&self.limiter, Endpoint::PostOrder, "post order", || {
+                let order_args = &order_args;
+                async move {
+                    let client = self.client.lock().await;
+                    let sign_started = Instant::now();
+                    let signed = client
+                        .create_order(order_args, expiration, None, None)
+                        .await
+                        .map_err(|e| (classify_error(&e), format!("create order: {:?}", e)))?;
+                    let sign = sign_started.elapsed();
+                    let post_started = Instant::now();
+                    let posted = client
+                        .post_order(signed, order_type)
+                        .await
+                        .map_err(|e| (classify_send_error(&e), format!("post order: {:?}", e)))?;
+                    let post = post_started.elapsed();
+                    match classify_post_response(&posted) {
+                        Some(rejection) => Err(rejection),
+                        None => Ok((posted, sign, post)),
+                    }
+                }
+            })
+            .await;
+            let reason = match result {
+                Ok(posted) => break posted,
+                Err((ErrorClass::Unconfirmed, reason)) if checks < MAX_UNCONFIRMED_CHECKS => reason,
+                Err(err) => return Err(err),
+            };
+            checks += 1;
+            logger::logln(format!(
+                "Exec: Post outcome unknown ({}); checking open orders before posting again",
+                reason
+            ));
+            if let Some(order_id) = self.find_resting(order, since).await? {
+                logger::logln(format!(
+                    "Exec: Unconfirmed post of {:?} {} @ {:.4} is resting as {}",
+                    order.side, order.asset_id, order.price, order_id
+                ));
+                return Ok(Placement {
+                    order_id,
+                    sign: None,
+                    post: None,
+                });
+            }
+        };
+
+        logger::logln(format!("Exec: Posted order: {:?}", posted));
+        posted
+            .get("orderID")
+            .or_else(|| posted.get("order_id"))
+            .and_then(|v| v.as_str())
+            .map(|id| Placement {
+                order_id: id.to_string(),
+                sign: Some(sign),
+                post: Some(post),
+            })
+            .ok_or_else(|| {
+                (
+                    ErrorClass::Fatal,
+                    format!("no order ID returned. Response: {:?}", posted),
+                )
+            })
+    }
+
+    async fn cancel(&self, order_id: &str) -> ExecResult<()> {
+        with_retry(
+            &self.limiter,
+            Endpoint::CancelOrder,
+            "cancel order",
+            || async move {
+                self.client
+                    .lock()
+                    .await
+                    .cancel(order_id)
+                    .await
+                    .map(|_| ())
+                    .map_err(|e| (classify_error(&e), format!("{:?}", e)))
+            },
+        )
+        .await
+    }
+
+    async fn cancel_all(&self) -> ExecResult<()> {
+        with_retry(
+            &self.limiter,
+            Endpoint::CancelAll,
+            "cancel all",
+            || async move {
+                self.client
+                    .lock()
+                    .await
+                    .cancel_all()
+                    .await
+                    .map(|_| ())
+                    .map_err(|e| (classify_error(&e), format!("{:?}", e)))
+            },
+        )
+        .await
+    }
+
+    async fn order_is_live(&self, order_id: &str) -> ExecResult<bool> {
+        let order = with_retry(
+            &self.limiter,
+            Endpoint::GetOrder,
+            "get order",
+            || async move {
+                self.client
+                    .lock()
+                    .await
+                    .get_order(order_id)
+                    .await
+                    .map_err(|e| (classify_error(&e), format!("{:?}", e)))
+            },
+        )
+        .await?;
+        Ok(order.status.eq_ignore_ascii_case(ORDER_STATUS_LIVE))
+    }
+
+    async fn merge(&self, shares: i64, condition_id: &str, proxy_wallet: &str) -> ExecResult<()> {
+        split_merge::execute_split_merge(
+            TransactionType::Merge,
+            shares.to_string().as_str(),
+            condition_id,
+            proxy_wallet,
+            false,
+        )
+        .await
+        .map(|_| ())
+        .map_err(|e| (ErrorClass::Fatal, format!("{:?}", e)))
+    }
+
+    async fn split(&self, shares: i64, condition_id: &str, proxy_wallet: &str) -> ExecResult<()> {
+        split_merge::execute_split_merge(
+            TransactionType::Split,
+            shares.to_string().as_str(),
+            condition_id,
+            proxy_wallet,
+            false,
+        )
+        .await
+        .map(|_| ())
+        .map_err(|e| (ErrorClass::Fatal, format!("{:?}", e)))
+    }
+}
 
 // -------------------- Order Execution Task --------------------
-pub async fn order_execution_task(
-    mut cmd_rx: mpsc::Receiver<BotCommand>,
-    client_pm: Arc<Mutex<ClobClient>>,
+pub async fn order_execution_task<B: ExecutionBackend>(
+    mut cmd_rx: mpsc::Receiver<TimedCommand>,
+    backend: B,
     state: Arc<Mutex<AppState>>,
 ) {
-    while let Some(cmd) = cmd_rx.recv().await {
+    logger::logln(format!("Exec: Using {} execution backend", backend.name()));
+    // Merges and splits wait for a Safe transaction; they run on their own tasks so cancels
+    // and quotes keep flowing, and report back through state
+    let backend = Arc::new(backend);
+    let mut chain_tasks: Vec<JoinHandle<()>> = Vec::new();
+    let mut reconcile_timer = tokio::time::interval(Duration::from_millis(RECONCILE_INTERVAL_MS));
+    loop {
+        let cmd = tokio::select! {
+            _ = reconcile_timer.tick() => {
+                reconcile_pending_cancels(backend.as_ref(), &state).await;
+                continue;
+            }
+            cmd = cmd_rx.recv() => cmd,
+        };
+        let Some(TimedCommand { cmd, emitted_at }) = cmd else {
+            break;
+        };
+        state
+            .lock()
+            .await
+            .latency
+            .record(LatencyStage::Queue, emitted_at.elapsed());
         match cmd {
             BotCommand::Create(order) => {
-                // Convert our internal Order to Polymarket OrderArgs
-                let pm_side = match order.side {
-                    Side::Buy => PmSide::BUY,
-                    Side::Sell => PmSide::SELL,
-                };
+                // The quote stays in `pending_creates` until it is resting or given up on,
+                // so the trading task does not send it again while the POST is in flight.
+                // A Create queued before its market was stopped is dropped.
+                if state.lock().await.yes_token_of(&order.asset_id).is_none() {
+                    logger::logln(format!(
+                        "Exec: Dropping {:?} {} @ {:.4}: its market is no longer running",
+                        order.side, order.asset_id, order.price
+                    ));
+                    continue;
+                }
 
-                let order_args = OrderArgs {
-                    token_id: order.asset_id.clone(),
-                    price: Decimal::from_f64_retain(order.price).unwrap_or_default(),
-                    size: Decimal::from_f64_retain(order.size).unwrap_or_default(),
-                    side: pm_side,
-                };
+                // Never add quotes while we are unsure what is still resting
+                if state.lock().await.quoting_blocked() {
+                    logger::logln(format!(
+                        "Exec: Skipping {:?} {} @ {:.4}: quoting blocked until cancels are confirmed",
+                        order.side, order.asset_id, order.price
+                    ));
+                    state.lock().await.take_pending_create(&order);
+                    continue;
+                }
 
-                // Create and post order using Polymarket client
-                let client = client_pm.lock().await;
-                // Create signed order (proxy-safe) and then post it
-                let expiration: Option<u64> = None;
-                match client
-                    .create_order(&order_args, expiration, None, None)
-                    .await
-                {
-                    Ok(response) => {
-                        // Post order as GTC
-                        match client.post_order(response, OrderType::GTC).await {
-                            Ok(posted) => {
-                                logger::logln(format!("Exec: Posted order: {:?}", posted));
-                                if let Some(order_id_str) = posted
-                                    .get("orderID")
-                                    .or_else(|| posted.get("order_id"))
-                                    .and_then(|v| v.as_str())
-                                {
-                                    let order_id = order_id_str.to_string();
-                                    let mut s = state.lock().await;
-                                    let mut updated_order = order;
-                                    updated_order.id = Some(order_id.clone());
-                                    s.my_open_orders.insert(order_id, updated_order);
-                                    logger::logln(format!(
-                                        "Exec: Placed order successfully with ID: {}",
-                                        order_id_str
-                                    ));
-                                } else {
-                                    logger::logln(format!("Exec: Order did not go through - no order ID returned. Response: {:?}", posted));
-                                }
-                            }
-                            Err(e) => {
-                                logger::logln(format!("Exec: Failed to post order: {:?}", e));
-                            }
-                        }
-                    }
-                    Err(e) => {
-                        logger::logln(format!("Exec: Failed to create order: {:?}", e));
+                // Pre-trade risk gate: nothing reaches the signer without passing it
+                let (verdict, level_before) = {
+                    let mut s = state.lock().await;
+                    let verdict = risk::check_order(&order, &s);
+                    if verdict.is_err() {
+                        s.take_pending_create(&order);
                     }
+                    (verdict, queue::level_size(&s, &order))
+                };
+                if let Err(reason) = verdict {
+                    logger::logln(format!(
+                        "Risk: Rejected {:?} {} @ {:.4} x {:.2}: {}",
+                        order.side, order.asset_id, order.price, order.size, reason
+                    ));
+                    continue;
                 }
-            }
-            BotCommand::Cancel(order_id) => {
-                let client = client_pm.lock().await;
-                match client.cancel(&order_id).await {
-                    Ok(_) => {
+
+                let ttl_secs = state.lock().await.order_ttl_secs;
+                let expiration = heartbeat::order_expiration(ttl_secs);
+                match backend.place_order(&order, expiration).await {
+                    Ok(placement) => {
+                        let order_id = placement.order_id;
                         let mut s = state.lock().await;
-                        s.my_open_orders.remove(&order_id);
-                        logger::logln(format!("Exec: Canceled order {}", order_id));
+                        s.latency
+                            .record(LatencyStage::RoundTrip, emitted_at.elapsed());
+                        if let Some(sign) = placement.sign {
+                            s.latency.record(LatencyStage::Sign, sign);
+                        }
+                        if let Some(post) = placement.post {
+                            s.latency.record(LatencyStage::Post, post);
+                        }
+                        if backend.reports_placements() {
+                            s.latency.expect_placement(&order_id, emitted_at);
+                        }
+                        s.take_pending_create(&order);
+                        let mut updated_order = order;
+                        updated_order.id = Some(order_id.clone());
+                        s.my_open_orders.insert(order_id.clone(), updated_order);
+                        queue::on_placed(&mut s, &order_id, level_before);
+                        if let Some(exp) = expiration {
+                            s.order_expiries.insert(order_id.clone(), exp as i64);
+                        }
+                        logger::logln(format!(
+                            "Exec: Placed order successfully with ID: {}",
+                            order_id
+                        ));
                     }
-                    Err(e) => {
+                    Err((class, reason)) => {
+                        state.lock().await.take_pending_create(&order);
                         logger::logln(format!(
-                            "Exec: Failed to cancel order {}: {:?}",
-                            order_id, e
+                            "Exec: Order {:?} {} @ {:.4} x {:.2} failed ({:?}): {}",
+                            order.side, order.asset_id, order.price, order.size, class, reason
                         ));
                     }
                 }
             }
-            BotCommand::CancelAll => {
-                let client = client_pm.lock().await;
-                match client.cancel_all().await {
-                    Ok(_) => {
-                        let mut s = state.lock().await;
-                        s.my_open_orders.clear();
-                        logger::logln("Exec: Canceled all orders".to_string());
-                    }
-                    Err(e) => {
-                        logger::logln(format!("Exec: Failed to cancel all orders: {:?}", e));
-                        // Fallback: clear local state even if API call failed
-                        let mut s = state.lock().await;
-                        s.my_open_orders.clear();
+            BotCommand::Cancel(order_id) => match timed(backend.cancel(&order_id)).await {
+                (Ok(()), elapsed) => {
+                    let mut s = state.lock().await;
+                    s.latency.record(LatencyStage::Cancel, elapsed);
+                    s.forget_order(&order_id);
+                    logger::logln(format!("Exec: Canceled order {}", order_id));
+                }
+                (Err((class, reason)), _) => {
+                    logger::logln(format!(
+                        "Exec: Failed to cancel order {} ({:?}): {}. Marked cancel-pending.",
+                        order_id, class, reason
+                    ));
+                    let mut s = state.lock().await;
+                    s.mark_cancel_pending(&order_id, CancelState::Pending, now_millis());
+                }
+            },
+            BotCommand::CancelAll => match timed(backend.cancel_all()).await {
+                (Ok(()), elapsed) => {
+                    let mut s = state.lock().await;
+                    s.latency.record(LatencyStage::CancelAll, elapsed);
+                    s.latency.forget_all();
+                    s.my_open_orders.clear();
+                    s.order_expiries.clear();
+                    s.cancel_pending.clear();
+                    s.cancel_all_pending = false;
+                    logger::logln("Exec: Canceled all orders".to_string());
+                }
+                (Err((class, reason)), _) => {
+                    // Keep local orders: they may still be live. Retry and reconcile instead.
+                    let mut s = state.lock().await;
+                    let now = now_millis();
+                    let ids: Vec<OrderId> = s.my_open_orders.keys().cloned().collect();
+                    for id in &ids {
+                        s.mark_cancel_pending(id, CancelState::Pending, now);
                     }
+                    s.cancel_all_pending = true;
+                    logger::logln(format!(
+                        "Exec: Failed to cancel all orders ({:?}): {}. {} orders marked cancel-pending; quoting blocked.",
+                        class,
+                        reason,
+                        ids.len()
+                    ));
                 }
-            }
+            },
             BotCommand::OrderFilled(order_id, token_id, side, price, size) => {
                 // Update inventory when order is filled
                 let mut s = state.lock().await;
@@ -114,7 +469,7 @@ pub async fn order_execution_task(
                 };
                 s.inventory
                     .insert(token_id.clone(), current_inventory + inventory_change);
-                s.my_open_orders.remove(&order_id);
+                s.forget_order(&order_id);
                 logger::logln(format!(
                     "Exec: Order {} filled - {} {} {} tokens @ {:.4}",
                     order_id,
@@ -124,33 +479,56 @@ pub async fn order_execution_task(
                     price
                 ));
             }
-            BotCommand::Shutdown(condition_id, proxy_wallet) => {
+            BotCommand::Merge(market, proxy_wallet, shares) => {
+                chain_tasks.retain(|task| !task.is_finished());
+                chain_tasks.push(tokio::spawn(merge_pairs(
+                    Arc::clone(&backend),
+                    Arc::clone(&state),
+                    market,
+                    proxy_wallet,
+                    shares,
+                )));
+            }
+            BotCommand::Split(market, proxy_wallet, shares) => {
+                chain_tasks.retain(|task| !task.is_finished());
+                chain_tasks.push(tokio::spawn(split_usdc(
+                    Arc::clone(&backend),
+                    Arc::clone(&state),
+                    market,
+                    proxy_wallet,
+                    shares,
+                )));
+            }
+            BotCommand::Shutdown(proxy_wallet) => {
                 logger::logln("Exec: Shutdown received - merging before exit".to_string());
-                let min_f64 = {
+                // Let in-flight merges and splits land first so their pairs are counted once
+                for task in chain_tasks.drain(..) {
+                    let _ = task.await;
+                }
+                let merges: Vec<(String, i64)> = {
                     let s = state.lock().await;
-                    s.inventory
+                    s.markets
                         .values()
-                        .copied()
-                        .fold(f64::INFINITY, f64::min)
-                        .abs()
+                        .map(|m| {
+                            let pairs = auto_merge::free_pairs(&s, &m.yes_token);
+                            (m.condition_id.clone(), pairs as i64)
+                        })
+                        .collect()
                 };
-                let shares_to_merge_i64: i64 = min_f64.floor() as i64;
-                if shares_to_merge_i64 > 0 {
-                    match split_merge::execute_split_merge(
-                        TransactionType::Merge,
-                        shares_to_merge_i64.to_string().as_str(),
-                        &condition_id,
-                        &proxy_wallet,
-                        false,
-                    )
-                    .await
+                for (condition_id, shares_to_merge_i64) in merges {
+                    if shares_to_merge_i64 <= 0 {
+                        continue;
+                    }
+                    match backend
+                        .merge(shares_to_merge_i64, &condition_id, &proxy_wallet)
+                        .await
                     {
-                        Ok(_) => logger::logln(format!(
-                            "Exec: Merged {shares_to_merge_i64} shares successfully"
+                        Ok(()) => logger::logln(format!(
+                            "Exec: Merged {shares_to_merge_i64} shares of {condition_id} successfully"
                         )),
-                        Err(e) => logger::logln(format!(
-                            "Exec: Failed to merge {shares_to_merge_i64} shares: {:?}",
-                            e
+                        Err((_, reason)) => logger::logln(format!(
+                            "Exec: Failed to merge {shares_to_merge_i64} shares of {condition_id}: {}",
+                            reason
                         )),
                     }
                 }
@@ -160,4 +538,152 @@ pub async fn order_execution_task(
     }
 }
 
+// -------------------- Merge / Split --------------------
+/// Merge `shares` pairs of the market and settle inventory; the pairs stay reserved in
+/// `merge_reserved` until the transaction is done. Settled even if the market stopped meanwhile:
+/// its inventory is kept for a restart.
+async fn merge_pairs<B: ExecutionBackend>(
+    backend: Arc<B>,
+    state: Arc<Mutex<AppState>>,
+    market: MarketSpec,
+    proxy_wallet: String,
+    shares: i64,
+) {
+    let result = backend
+        .merge(shares, &market.condition_id, &proxy_wallet)
+        .await;
+    let mut s = state.lock().await;
+    s.merge_reserved.remove(&market.yes_token);
+    match result {
+        Ok(()) => {
+            // Each pair turns one YES and one NO back into 1 USDC
+            for token in market.tokens() {
+                if let Some(held) = s.inventory.get_mut(token) {
+                    *held -= shares as f64;
+                }
+            }
+            limits::record_cash(&mut s, &market.yes_token, shares as f64);
+            logger::logln(format!(
+                "Exec: Merged {} pairs back to USDC while running",
+                shares
+            ));
+        }
+        Err((class, reason)) => logger::logln(format!(
+            "Exec: Failed to merge {} pairs ({:?}): {}",
+            shares, class, reason
+        )),
+    }
+}
+
+/// Split `shares` USDC into pairs of the market; `split_pending` is cleared once done, and
+/// inventory settled even if the market stopped meanwhile
+async fn split_usdc<B: ExecutionBackend>(
+    backend: Arc<B>,
+    state: Arc<Mutex<AppState>>,
+    market: MarketSpec,
+    proxy_wallet: String,
+    shares: i64,
+) {
+    let result = backend
+        .split(shares, &market.condition_id, &proxy_wallet)
+        .await;
+    let mut s = state.lock().await;
+    s.split_pending.remove(&market.yes_token);
+    match result {
+        Ok(()) => {
+            // Splitting N USDC yields N YES and N NO
+            for token in market.tokens() {
+                *s.inventory.entry(token.clone()).or_insert(0.0) += shares as f64;
+            }
+            limits::record_cash(&mut s, &market.yes_token, -(shares as f64));
+            logger::logln(format!("Exec: Split {} USDC into YES/NO pairs", shares));
+        }
+        Err((class, reason)) => logger::logln(format!(
+            "Exec: Failed to split {} USDC ({:?}): {}",
+            shares, class, reason
+        )),
+    }
+}
+
+// -------------------- Cancel Reconciliation --------------------
+/// Retry unconfirmed cancels and check the exchange until every order's fate is known.
+/// A failed CancelAll is retried as a whole; single cancels are checked order by order
+/// with exponential backoff per order.
+async fn reconcile_pending_cancels<B: ExecutionBackend>(backend: &B, state: &Arc<Mutex<AppState>>) {
+    let (cancel_all, pending) = {
+        let s = state.lock().await;
+        if !s.quoting_blocked() {
+            return;
+        }
+        (s.cancel_all_pending, s.cancel_pending.clone())
+    };
+
+    if cancel_all {
+        match backend.cancel_all().await {
+            Ok(()) => {
+                let mut s = state.lock().await;
+                s.latency.forget_all();
+                s.my_open_orders.clear();
+                s.order_expiries.clear();
+                s.cancel_pending.clear();
+                s.cancel_all_pending = false;
+                logger::logln(
+                    "Exec: Reconcile: cancel-all confirmed. Quoting unblocked.".to_string(),
+                );
+            }
+            Err((class, reason)) => {
+                logger::logln(format!(
+                    "Exec: Reconcile: cancel-all retry failed ({:?}): {}",
+                    class, reason
+                ));
+            }
+        }
+        return;
+    }
+
+    let now = now_millis();
+    for (order_id, p) in pending {
+        let backoff_ms = (RECONCILE_INTERVAL_MS as i64)
+            .saturating_mul(1i64 << p.attempts.min(10))
+            .min(RECONCILE_MAX_BACKOFF_MS);
+        if now - p.last_attempt_ms < backoff_ms {
+            continue;
+        }
+        let outcome = match backend.order_is_live(&order_id).await {
+            Ok(false) => Ok("no longer live on the exchange"),
+            Ok(true) => match backend.cancel(&order_id).await {
+                Ok(()) => Ok("canceled on retry"),
+                Err((_, reason)) => Err((CancelState::Pending, reason)),
+            },
+            Err((_, reason)) => Err((CancelState::Unknown, reason)),
+        };
+        let mut s = state.lock().await;
+        match outcome {
+            Ok(how) => {
+                s.forget_order(&order_id);
+                logger::logln(format!("Exec: Reconcile: order {} {}", order_id, how));
+            }
+            Err((cancel_state, reason)) => {
+                s.mark_cancel_pending(&order_id, cancel_state, now_millis());
+                logger::logln(format!(
+                    "Exec: Reconcile: order {} still {:?} after {} attempts: {}",
+                    order_id,
+                    cancel_state,
+                    p.attempts + 1,
+                    reason
+                ));
+            }
+        }
+    }
+    if !state.lock().await.quoting_blocked() {
+        logger::logln("Exec: Reconcile: all cancels confirmed. Quoting unblocked.".to_string());
+    }
+}
+
+async fn timed<T>(fut: impl Future<Output = T>) -> (T, Duration) {
+    let started = Instant::now();
+    let out = fut.await;
+    (out, started.elapsed())
+}
+
 // Position/Open Orders helpers moved to data.rs
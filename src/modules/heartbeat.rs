@@ -0,0 +1,121 @@
+use crate::modules::logger;
+use crate::modules::pause::{self, PauseReason};
+use crate::modules::types::{now_millis, AppState, BotCommand, CommandSender};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tokio::time::Duration;
+
+// -------------------- Dead-Man Switch Config --------------------
+const CHECK_INTERVAL_SECS: u64 = 1;
+const TRADING_TIMEOUT_MS: i64 = 10_000; // trading loop beats every second from its own timer
+const MARKET_FEED_TIMEOUT_MS: i64 = 30_000; // market WS is pinged every 10s, so PONGs keep it beating
+const USER_FEED_TIMEOUT_MS: i64 = 30_000;
+/// The CLOB rejects GTD expirations closer than one minute away; the effective
+/// lifetime of an order is this buffer plus `AppState.order_ttl_secs`.
+pub const GTD_SECURITY_BUFFER_SECS: u64 = 60;
+const RENEW_MARGIN_SECS: i64 = 20; // re-post orders this long before they expire
+const DEAD_MAN_COOLDOWN_SECS: u64 = 5; // all components healthy this long before quoting resumes
+
+/// Tasks that must keep beating for the bot to be considered healthy
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Component {
+    Trading,
+    MarketFeed,
+    UserFeed,
+}
+
+impl Component {
+    fn timeout_ms(&self) -> i64 {
+        match self {
+            Component::Trading => TRADING_TIMEOUT_MS,
+            Component::MarketFeed => MARKET_FEED_TIMEOUT_MS,
+            Component::UserFeed => USER_FEED_TIMEOUT_MS,
+        }
+    }
+}
+
+/// Record that `component` is alive
+pub async fn beat(state: &Arc<Mutex<AppState>>, component: Component) {
+    let mut s = state.lock().await;
+    s.heartbeats.insert(component, now_millis());
+}
+
+/// Expiration (unix seconds) for a new order, or None for GTC when the safety net is off
+pub fn order_expiration(ttl_secs: u64) -> Option<u64> {
+    if ttl_secs == 0 {
+        return None;
+    }
+    Some((now_millis() / 1000) as u64 + GTD_SECURITY_BUFFER_SECS + ttl_secs)
+}
+
+/// Whether `order_id` lapses within `RENEW_MARGIN_SECS`. The trading task re-posts such orders
+/// through its quote diff, so the fresh order is tracked like any other new quote.
+pub fn renewal_due(s: &AppState, order_id: &str) -> bool {
+    s.order_expiries
+        .get(order_id)
+        .is_some_and(|exp| *exp - now_millis() / 1000 <= RENEW_MARGIN_SECS)
+}
+
+// -------------------- Heartbeat Task --------------------
+/// Watches component heartbeats. When any component goes stale it pauses quoting and cancels
+/// everything, resuming once every component has been healthy for `DEAD_MAN_COOLDOWN_SECS`;
+/// the pause also holds back renewals (`renewal_due`). If this task itself hangs, the GTD
+/// expirations make the exchange pull our orders on its own.
+pub async fn heartbeat_task(state: Arc<Mutex<AppState>>, cmd_tx: CommandSender) {
+    let mut interval = tokio::time::interval(Duration::from_secs(CHECK_INTERVAL_SECS));
+    loop {
+        interval.tick().await;
+        let now = now_millis();
+
+        let stale = {
+            let mut s = state.lock().await;
+            if s.shutting_down {
+                break;
+            }
+            let stale: Vec<(Component, i64)> = s
+                .heartbeats
+                .iter()
+                .filter(|(c, last)| now - **last > c.timeout_ms())
+                .map(|(c, last)| (*c, now - *last))
+                .collect();
+
+            if !stale.is_empty() {
+                if s.dead_man_tripped {
+                    continue;
+                }
+                s.dead_man_tripped = true;
+                let silent: Vec<String> = stale.iter().map(|(c, _)| format!("{:?}", c)).collect();
+                pause::pause(
+                    &mut s,
+                    PauseReason::DeadMan,
+                    DEAD_MAN_COOLDOWN_SECS,
+                    &format!("{} silent", silent.join(", ")),
+                );
+                stale
+            } else {
+                if s.dead_man_tripped {
+                    s.dead_man_tripped = false;
+                    logger::logln("Heartbeat: All components healthy again.".to_string());
+                }
+                pause::observe_recovery(&mut s, PauseReason::DeadMan, true);
+                // Drop expiries for orders that are gone
+                let live = s.my_open_orders.clone();
+                s.order_expiries.retain(|id, _| live.contains_key(id));
+                Vec::new()
+            }
+        };
+
+        if !stale.is_empty() {
+            for (component, age_ms) in &stale {
+                logger::logln(format!(
+                    "Heartbeat: {:?} silent for {}ms. Dead-man switch tripped; canceling all orders.",
+                    component, age_ms
+                ));
+            }
+            if cmd_tx.send(BotCommand::CancelAll).await.is_err() {
+                return;
+            }
+        }
+    }
+}
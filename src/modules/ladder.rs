@@ -0,0 +1,207 @@
+use crate::modules::strategy::{complement_price, yes_quote, yes_side, SellBudget, YesBook};
+use crate::modules::types::{AppState, Order, Side};
+use serde::{Deserialize, Serialize};
+
+// -------------------- Ladder Defaults --------------------
+const DEFAULT_LEVELS: u32 = 1; // one order per side
+const DEFAULT_SPACING_TICKS: u32 = 1;
+const DEFAULT_SIZE_GROWTH: f64 = 1.0;
+pub const MAX_LEVELS: u32 = 20;
+const SIZE_DECIMALS: f64 = 100.0; // sizes are rounded to 0.01 shares
+const QUOTE_EPSILON: f64 = 1e-9;
+
+/// `ladder` in the config
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LadderConfig {
+    pub levels: u32,        // orders per side
+    pub spacing_ticks: u32, // distance between consecutive levels
+    pub size_growth: f64,   // level i is order_size * size_growth^i
+}
+
+impl Default for LadderConfig {
+    fn default() -> Self {
+        Self {
+            levels: DEFAULT_LEVELS,
+            spacing_ticks: DEFAULT_SPACING_TICKS,
+            size_growth: DEFAULT_SIZE_GROWTH,
+        }
+    }
+}
+
+impl LadderConfig {
+    /// Shares one side of a full ladder of `order_size` quotes holds
+    pub fn side_shares(&self, order_size: f64) -> f64 {
+        (0..self.levels.max(1))
+            .map(|level| order_size * self.size_growth.powi(level as i32))
+            .sum()
+    }
+}
+
+/// Build both sides of a ladder from the inner YES bid/ask outward, `spacing_ticks` apart, level
+/// `i` sized `order_size * size_growth^i`. A level at a price we already quote (resting or in
+/// flight, at one of the ladder's sizes) keeps that order's size and token and is funded first,
+/// so when the inner quote moves by a whole number of levels only the edge orders are created
+/// and canceled, whatever `size_growth` is; a wider ladder moved by fewer ticks than its
+/// spacing is replaced entirely. Other sells are funded from inventory level by level,
+/// innermost first. Levels that would leave (0, 1) are dropped.
+pub fn yes_ladder(
+    book: &YesBook,
+    bid: f64,
+    ask: f64,
+    order_size: f64,
+    ladder: &LadderConfig,
+    state: &AppState,
+) -> Vec<Order> {
+    let tick = state.tick_size(&book.yes_token);
+    let ticks_per_unit = (1.0 / tick).round();
+    let bid_ticks = (bid * ticks_per_unit).round();
+    let ask_ticks = (ask * ticks_per_unit).round();
+    let sizes: Vec<f64> = (0..ladder.levels.max(1))
+        .map(|level| {
+            (order_size * ladder.size_growth.powi(level as i32) * SIZE_DECIMALS).round()
+                / SIZE_DECIMALS
+        })
+        .collect();
+
+    // (YES side, YES price, size) innermost first
+    let mut levels = Vec::new();
+    for (level, size) in sizes.iter().enumerate() {
+        let offset = (level as u32 * ladder.spacing_ticks) as f64;
+        let level_bid = (bid_ticks - offset) / ticks_per_unit;
+        let level_ask = (ask_ticks + offset) / ticks_per_unit;
+        if level_bid >= tick {
+            levels.push((Side::Buy, level_bid, *size));
+        }
+        if level_ask <= 1.0 - tick {
+            levels.push((Side::Sell, level_ask, *size));
+        }
+    }
+
+    let mut budget = SellBudget::new(book, state);
+    let mut orders: Vec<Option<Order>> = levels
+        .iter()
+        .map(|(side, price, _)| {
+            quoted_at(book, side, *price, &sizes, state).filter(|o| budget.reserve(book, o))
+        })
+        .collect();
+    for (order, (side, price, size)) in orders.iter_mut().zip(levels) {
+        if order.is_none() {
+            *order = Some(yes_quote(book, side, price, size, &mut budget, state));
+        }
+    }
+    orders.into_iter().flatten().collect()
+}
+
+/// Our order quoting `side` at YES `price` with one of the ladder's `sizes`, resting (cancel
+/// not requested) or in flight, as a new quote
+fn quoted_at(
+    book: &YesBook,
+    side: &Side,
+    price: f64,
+    sizes: &[f64],
+    state: &AppState,
+) -> Option<Order> {
+    let tick = state.tick_size(&book.yes_token);
+    let resting = state
+        .my_open_orders
+        .iter()
+        .filter(|(id, _)| !state.cancel_pending.contains_key(*id))
+        .map(|(_, o)| o);
+    resting
+        .chain(state.pending_creates.iter())
+        .find(|o| {
+            let yes_price = if o.asset_id == book.yes_token {
+                o.price
+            } else if o.asset_id == book.no_token {
+                complement_price(o.price, tick)
+            } else {
+                return false;
+            };
+            yes_side(book, o) == *side
+                && (yes_price - price).abs() < QUOTE_EPSILON
+                && sizes
+                    .iter()
+                    .any(|size| (size - o.size).abs() < QUOTE_EPSILON)
+        })
+        .map(|o| Order {
+            id: None,
+            ..o.clone()
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::test_support::{add_market, rest};
+
+    const SIZE: f64 = 10.0;
+
+    fn geometric() -> LadderConfig {
+        LadderConfig {
+            levels: 3,
+            spacing_ticks: 1,
+            size_growth: 1.5,
+        }
+    }
+
+    /// A market holding `no_held` NO shares and resting the ladder quoted at `bid`/`ask`
+    fn resting_ladder(no_held: f64, bid: f64, ask: f64) -> (AppState, YesBook) {
+        let mut s = AppState::default();
+        let no_token = add_market(&mut s, "yes", 0.49, 0.51);
+        s.inventory.insert(no_token.clone(), no_held);
+        let book = YesBook {
+            yes_token: "yes".to_string(),
+            no_token,
+            best_bid: 0.49,
+            best_ask: 0.51,
+        };
+        for (i, order) in yes_ladder(&book, bid, ask, SIZE, &geometric(), &s)
+            .into_iter()
+            .enumerate()
+        {
+            rest(&mut s, &format!("o{}", i), order);
+        }
+        (s, book)
+    }
+
+    /// (creates, cancels) the requote diff needs to go from the resting orders to `desired`
+    fn diff(s: &AppState, mut desired: Vec<Order>) -> (Vec<Order>, Vec<Order>) {
+        let mut cancels = Vec::new();
+        for order in s.my_open_orders.values() {
+            match desired.iter().position(|d| d.same_quote(order)) {
+                Some(pos) => {
+                    desired.remove(pos);
+                }
+                None => cancels.push(order.clone()),
+            }
+        }
+        (desired, cancels)
+    }
+
+    #[test]
+    fn one_level_shift_only_moves_the_edges() {
+        // Unfunded, and with NO for the inner bid only: the shift must not re-fund the levels
+        for no_held in [0.0, SIZE] {
+            let (s, book) = resting_ladder(no_held, 0.45, 0.55);
+            let shifted = yes_ladder(&book, 0.44, 0.54, SIZE, &geometric(), &s);
+            let (creates, cancels) = diff(&s, shifted);
+            let side = |o: &Order| yes_side(&book, o);
+            assert_eq!(creates.len(), 2, "no_held {}: {:?}", no_held, creates);
+            assert_eq!(cancels.len(), 2, "no_held {}: {:?}", no_held, cancels);
+            // The new outer bid and inner ask are created; the old inner bid and outer ask go
+            assert!(creates
+                .iter()
+                .any(|o| side(o) == Side::Buy && o.size == 22.5));
+            assert!(creates
+                .iter()
+                .any(|o| side(o) == Side::Sell && o.size == SIZE));
+            assert!(cancels
+                .iter()
+                .any(|o| side(o) == Side::Buy && o.size == SIZE));
+            assert!(cancels
+                .iter()
+                .any(|o| side(o) == Side::Sell && o.size == 22.5));
+        }
+    }
+}
@@ -0,0 +1,563 @@
+use crate::modules::logger;
+use crate::modules::markout;
+use crate::modules::risk::RiskRejection;
+use crate::modules::strategy::Fill;
+use crate::modules::types::{AppState, Order, Side};
+use chrono::{NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::time::Instant;
+
+// -------------------- Limit Defaults --------------------
+const DEFAULT_ACCOUNT_MAX_NOTIONAL: f64 = 1000.0; // room for two markets' full ladders at the defaults
+const DEFAULT_ACCOUNT_MAX_OPEN_NOTIONAL: f64 = 5000.0;
+const RESUME_FRACTION: f64 = 0.9; // a position breach clears once usage is back under 90% of its limit
+
+/// What a breached scope's markets do until the breach clears
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BreachAction {
+    /// Pull every quote
+    Pause,
+    /// Keep only quotes that shrink each market's unpaired position
+    ReduceOnly,
+    /// Sell each market's unpaired position through the unwind path (`unwind.rs`)
+    Flatten,
+}
+
+/// One layer of limits (`limits` per market, `portfolio.group`, `portfolio.account`).
+/// Unset limits are not enforced.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LimitSet {
+    pub max_notional: Option<f64>, // dollars of unpaired YES/NO, valued at the mid
+    pub max_shares: Option<f64>,   // unpaired YES/NO shares
+    pub max_open_notional: Option<f64>, // resting + new order notional
+    pub max_daily_loss: Option<f64>, // realized + unrealized loss since 00:00 UTC
+    pub action: BreachAction,      // on a notional or shares breach
+    pub loss_action: BreachAction, // on a daily loss breach; holds until the next UTC day
+}
+
+impl Default for LimitSet {
+    fn default() -> Self {
+        Self {
+            max_notional: None,
+            max_shares: None,
+            max_open_notional: None,
+            max_daily_loss: None,
+            action: BreachAction::ReduceOnly,
+            loss_action: BreachAction::Pause,
+        }
+    }
+}
+
+impl LimitSet {
+    pub fn is_valid(&self) -> bool {
+        [
+            self.max_notional,
+            self.max_shares,
+            self.max_open_notional,
+            self.max_daily_loss,
+        ]
+        .iter()
+        .all(|max| max.is_none_or(|max| max >= 0.0))
+    }
+}
+
+/// `portfolio` in the config: the same limits for every event group, and the account's
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PortfolioLimits {
+    pub group: LimitSet,
+    pub account: LimitSet,
+}
+
+impl Default for PortfolioLimits {
+    fn default() -> Self {
+        Self {
+            group: LimitSet::default(),
+            account: LimitSet {
+                max_notional: Some(DEFAULT_ACCOUNT_MAX_NOTIONAL),
+                max_open_notional: Some(DEFAULT_ACCOUNT_MAX_OPEN_NOTIONAL),
+                ..LimitSet::default()
+            },
+        }
+    }
+}
+
+/// Which markets a layer of limits covers
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum LimitScope {
+    Market(String), // yes_token
+    Group(String),  // neg-risk event id or the configured event_group
+    Account,
+}
+
+impl fmt::Display for LimitScope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LimitScope::Market(yes_token) => write!(f, "market {}", yes_token),
+            LimitScope::Group(group) => write!(f, "group {}", group),
+            LimitScope::Account => write!(f, "account"),
+        }
+    }
+}
+
+/// A scope over one of its limits, and what its markets do about it
+#[derive(Clone, Debug)]
+pub struct Breach {
+    pub action: BreachAction,
+    pub reason: String,
+    pub since: Instant,
+    pub until_day: Option<NaiveDate>, // daily loss breaches hold until this UTC day starts
+}
+
+/// What a scope currently uses of its limits
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Usage {
+    pub notional: f64,
+    pub shares: f64,
+    pub open_notional: f64,
+    pub daily_pnl: f64, // markets whose PnL is not known yet count as 0
+}
+
+// -------------------- Daily PnL --------------------
+/// Realized plus unrealized PnL of one market since 00:00 UTC: cash in and out (fills,
+/// merges, splits) plus the change in the mid value of its YES/NO inventory
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DailyPnl {
+    pub day: NaiveDate,
+    start_value: Option<f64>, // inventory value when the day started (or first priced)
+    cash: f64,
+}
+
+/// Cash flow for `yes_token`'s market: positive when USDC comes in
+pub fn record_cash(s: &mut AppState, yes_token: &str, amount: f64) {
+    roll_day(s, yes_token);
+    if let Some(pnl) = s.daily_pnl.get_mut(yes_token) {
+        pnl.cash += amount;
+    }
+}
+
+/// A fill pays or receives price * size in USDC
+pub fn record_fill(s: &mut AppState, fill: &Fill) {
+    let Some(yes_token) = s.yes_token_of(&fill.asset_id).map(str::to_string) else {
+        return;
+    };
+    let amount = fill.price * fill.size;
+    let amount = match fill.side {
+        Side::Buy => -amount,
+        Side::Sell => amount,
+    };
+    record_cash(s, &yes_token, amount);
+}
+
+/// Today's PnL of `yes_token`'s market, once its inventory has been priced
+pub fn daily_pnl(s: &AppState, yes_token: &str) -> Option<f64> {
+    let pnl = s.daily_pnl.get(yes_token)?;
+    let start_value = pnl.start_value?;
+    Some(pnl.cash + market_value(s, yes_token)? - start_value)
+}
+
+/// Start a new day at 00:00 UTC, and take the opening value once a mid is known
+fn roll_day(s: &mut AppState, yes_token: &str) {
+    let today = Utc::now().date_naive();
+    let value = market_value(s, yes_token);
+    let pnl = s
+        .daily_pnl
+        .entry(yes_token.to_string())
+        .or_insert(DailyPnl {
+            day: today,
+            start_value: None,
+            cash: 0.0,
+        });
+    if pnl.day != today {
+        pnl.day = today;
+        pnl.start_value = None;
+        pnl.cash = 0.0;
+    }
+    if pnl.start_value.is_none() {
+        pnl.start_value = value;
+    }
+}
+
+/// YES and NO held, valued at the YES mid
+fn market_value(s: &AppState, yes_token: &str) -> Option<f64> {
+    let no_token = s.token_pairs.get(yes_token)?;
+    let mid = markout::yes_mid(s, yes_token)?;
+    let held = |token: &str| s.inventory.get(token).copied().unwrap_or(0.0);
+    Some(held(yes_token) * mid + held(no_token) * (1.0 - mid))
+}
+
+// -------------------- Scopes --------------------
+/// Event group of `yes_token`'s market: the config's `event_group`, else its neg-risk event
+pub fn group_of<'a>(s: &'a AppState, yes_token: &str) -> Option<&'a str> {
+    s.market_config(yes_token)
+        .event_group
+        .as_deref()
+        .or_else(|| s.event_groups.get(yes_token).map(String::as_str))
+}
+
+/// Every layer that covers `yes_token`'s market, with its limits
+pub fn scopes_of(s: &AppState, yes_token: &str) -> Vec<(LimitScope, LimitSet)> {
+    let mut scopes = vec![(
+        LimitScope::Market(yes_token.to_string()),
+        market_limits(s, yes_token),
+    )];
+    if let Some(group) = group_of(s, yes_token) {
+        scopes.push((
+            LimitScope::Group(group.to_string()),
+            s.config.portfolio.group.clone(),
+        ));
+    }
+    scopes.push((LimitScope::Account, s.config.portfolio.account.clone()));
+    scopes
+}
+
+/// A market's own limits. Its notional limit is `limits.max_notional`, else
+/// `max_position_size`, else one full ladder side valued at `risk.max_price`, so a market can
+/// always be filled on one whole side of the ladder it quotes.
+fn market_limits(s: &AppState, yes_token: &str) -> LimitSet {
+    let config = s.market_config(yes_token);
+    let full_ladder = config.ladder.side_shares(config.order_size) * config.risk.max_price;
+    LimitSet {
+        max_notional: config
+            .limits
+            .max_notional
+            .or(config.max_position_size)
+            .or(Some(full_ladder)),
+        ..config.limits.clone()
+    }
+}
+
+/// Running markets (YES tokens) inside `scope`
+fn markets_in(s: &AppState, scope: &LimitScope) -> Vec<String> {
+    s.markets
+        .keys()
+        .filter(|yes_token| match scope {
+            LimitScope::Market(market) => market == *yes_token,
+            LimitScope::Group(group) => group_of(s, yes_token) == Some(group.as_str()),
+            LimitScope::Account => true,
+        })
+        .cloned()
+        .collect()
+}
+
+/// Unpaired (notional, shares) of one market, with `yes_delta` more YES than held
+/// (negative = more NO). Paired YES/NO redeem for $1 together, so only the excess is at risk.
+fn unpaired(s: &AppState, yes_token: &str, yes_delta: f64) -> (f64, f64) {
+    let Some(no_token) = s.token_pairs.get(yes_token) else {
+        return (0.0, 0.0);
+    };
+    let held = |token: &str| s.inventory.get(token).copied().unwrap_or(0.0);
+    let excess = held(yes_token) - held(no_token) + yes_delta;
+    let mid = markout::yes_mid(s, yes_token).unwrap_or(0.5);
+    let price = if excess >= 0.0 { mid } else { 1.0 - mid };
+    (excess.abs() * price, excess.abs())
+}
+
+pub fn usage(s: &AppState, scope: &LimitScope) -> Usage {
+    let mut usage = Usage::default();
+    for yes_token in markets_in(s, scope) {
+        let (notional, shares) = unpaired(s, &yes_token, 0.0);
+        usage.notional += notional;
+        usage.shares += shares;
+        usage.daily_pnl += daily_pnl(s, &yes_token).unwrap_or(0.0);
+        let no_token = s.token_pairs.get(&yes_token);
+        usage.open_notional += s
+            .my_open_orders
+            .values()
+            .filter(|o| o.asset_id == yes_token || Some(&o.asset_id) == no_token)
+            .map(|o| o.price * o.size)
+            .sum::<f64>();
+    }
+    usage
+}
+
+/// YES shares an order adds if it fills: buying YES or selling NO adds YES, the reverse adds NO
+fn yes_delta(order: &Order, yes_token: &str) -> f64 {
+    match (order.asset_id == yes_token, &order.side) {
+        (true, Side::Buy) | (false, Side::Sell) => order.size,
+        (true, Side::Sell) | (false, Side::Buy) => -order.size,
+    }
+}
+
+/// Whether filling `order` shrinks its market's unpaired position
+pub fn reduces(s: &AppState, yes_token: &str, order: &Order) -> bool {
+    let (_, before) = unpaired(s, yes_token, 0.0);
+    let (_, after) = unpaired(s, yes_token, yes_delta(order, yes_token));
+    after < before
+}
+
+// -------------------- Gate --------------------
+/// Pre-trade check of every layer covering the order's market: open notional including the
+/// order, and unpaired notional and shares if it fills. Orders that shrink a position always
+/// pass the position checks.
+pub fn check_order(order: &Order, notional: f64, s: &AppState) -> Result<(), RiskRejection> {
+    let Some(yes_token) = s.yes_token_of(&order.asset_id) else {
+        return Ok(());
+    };
+    let (notional_before, shares_before) = unpaired(s, yes_token, 0.0);
+    let (notional_after, shares_after) = unpaired(s, yes_token, yes_delta(order, yes_token));
+    for (scope, limits) in scopes_of(s, yes_token) {
+        let used = usage(s, &scope);
+        let checks = [
+            (
+                "open notional",
+                used.open_notional + notional,
+                limits.max_open_notional,
+                true,
+            ),
+            (
+                "unpaired notional",
+                used.notional - notional_before + notional_after,
+                limits.max_notional,
+                notional_after > notional_before,
+            ),
+            (
+                "unpaired shares",
+                used.shares - shares_before + shares_after,
+                limits.max_shares,
+                shares_after > shares_before,
+            ),
+        ];
+        for (what, value, max, grows) in checks {
+            if let Some(max) = max.filter(|max| grows && value > *max) {
+                return Err(RiskRejection::Limit {
+                    scope,
+                    what,
+                    value,
+                    max,
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
+// -------------------- Breaches --------------------
+/// Check every scope against its limits; called once a second by the trading task. A
+/// notional or shares breach clears once usage is back under `RESUME_FRACTION` of the limit;
+/// a daily loss breach holds until the next UTC day. Markets re-quote from scratch when a
+/// breach covering them clears.
+pub fn review(s: &mut AppState) {
+    let markets: Vec<String> = s.markets.keys().cloned().collect();
+    let mut scopes: HashMap<LimitScope, LimitSet> = HashMap::new();
+    for yes_token in &markets {
+        roll_day(s, yes_token);
+        scopes.extend(scopes_of(s, yes_token));
+    }
+    let today = Utc::now().date_naive();
+    let mut cleared = Vec::new();
+
+    for (scope, limits) in scopes {
+        let used = usage(s, &scope);
+        let loss = -used.daily_pnl;
+        if let Some(max) = limits.max_daily_loss.filter(|max| loss > *max) {
+            let latched = s
+                .breaches
+                .get(&scope)
+                .is_some_and(|b| b.until_day.is_some());
+            if !latched {
+                trip(
+                    s,
+                    &scope,
+                    limits.loss_action,
+                    format!("daily loss ${:.2} over ${:.2}", loss, max),
+                    today.succ_opt(),
+                );
+            }
+            continue;
+        }
+
+        let over = |value: f64, max: Option<f64>, fraction: f64| {
+            max.is_some_and(|max| value > max * fraction)
+        };
+        let position_over = match (limits.max_notional, limits.max_shares) {
+            (Some(max), _) if used.notional > max => Some(format!(
+                "unpaired notional ${:.2} over ${:.2}",
+                used.notional, max
+            )),
+            (_, Some(max)) if used.shares > max => Some(format!(
+                "unpaired shares {:.2} over {:.2}",
+                used.shares, max
+            )),
+            _ => None,
+        };
+        let position_held = over(used.notional, limits.max_notional, RESUME_FRACTION)
+            || over(used.shares, limits.max_shares, RESUME_FRACTION);
+        match s.breaches.get(&scope) {
+            Some(breach) if breach.until_day.is_some_and(|day| day > today) => {}
+            Some(_) if position_held => {}
+            Some(_) => cleared.push(scope),
+            None => {
+                if let Some(reason) = position_over {
+                    trip(s, &scope, limits.action, reason, None);
+                }
+            }
+        }
+    }
+
+    // Scopes no running market belongs to any more have nothing left to breach
+    let stale: Vec<LimitScope> = s
+        .breaches
+        .keys()
+        .filter(|scope| markets_in(s, scope).is_empty())
+        .cloned()
+        .collect();
+    cleared.extend(stale);
+    for scope in cleared {
+        let Some(breach) = s.breaches.remove(&scope) else {
+            continue;
+        };
+        logger::logln(format!(
+            "Risk: {} back within limits after {}s ({})",
+            scope,
+            breach.since.elapsed().as_secs(),
+            breach.reason
+        ));
+        for yes_token in markets_in(s, &scope) {
+            s.last_mid_bucket.remove(&yes_token);
+        }
+    }
+}
+
+fn trip(
+    s: &mut AppState,
+    scope: &LimitScope,
+    action: BreachAction,
+    reason: String,
+    until_day: Option<NaiveDate>,
+) {
+    let kill = if until_day.is_some() && *scope == LimitScope::Account {
+        " Daily loss kill switch:"
+    } else {
+        ""
+    };
+    logger::logln(format!(
+        "Risk:{} {} breached its limits ({}); {:?}{}",
+        kill,
+        scope,
+        reason,
+        action,
+        until_day.map_or(String::new(), |day| format!(" until {}", day))
+    ));
+    s.breaches.insert(
+        scope.clone(),
+        Breach {
+            action,
+            reason,
+            since: Instant::now(),
+            until_day,
+        },
+    );
+}
+
+/// The strictest action of any breach covering `yes_token`'s market: `Pause`, then
+/// `Flatten`, then `ReduceOnly`
+pub fn restriction(s: &AppState, yes_token: &str) -> Option<BreachAction> {
+    let actions: Vec<BreachAction> = scopes_of(s, yes_token)
+        .iter()
+        .filter_map(|(scope, _)| s.breaches.get(scope).map(|b| b.action))
+        .collect();
+    [
+        BreachAction::Pause,
+        BreachAction::Flatten,
+        BreachAction::ReduceOnly,
+    ]
+    .into_iter()
+    .find(|action| actions.contains(action))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::test_support::{add_market, order};
+    use crate::modules::unwind;
+
+    const GROUP: &str = "event";
+
+    /// Markets "a" and "b" of one event group limited to 100 unpaired shares, with YES at 0.50
+    /// and `held` YES shares of each
+    fn grouped(held: [f64; 2]) -> AppState {
+        let mut s = AppState::default();
+        s.config.default.event_group = Some(GROUP.to_string());
+        s.config.portfolio.group.max_shares = Some(100.0);
+        for (yes_token, shares) in ["a", "b"].into_iter().zip(held) {
+            add_market(&mut s, yes_token, 0.49, 0.51);
+            s.inventory.insert(yes_token.to_string(), shares);
+        }
+        s
+    }
+
+    fn check(s: &AppState, order: &Order) -> Result<(), RiskRejection> {
+        check_order(order, order.price * order.size, s)
+    }
+
+    #[test]
+    fn order_breaching_a_group_is_rejected() {
+        let s = grouped([60.0, 35.0]);
+        assert!(check(&s, &order("a", Side::Buy, 0.50, 5.0)).is_ok());
+        match check(&s, &order("a", Side::Buy, 0.50, 10.0)) {
+            Err(RiskRejection::Limit { scope, what, .. }) => {
+                assert_eq!(scope, LimitScope::Group(GROUP.to_string()));
+                assert_eq!(what, "unpaired shares");
+            }
+            other => panic!("expected a group limit rejection, got {:?}", other),
+        }
+        // Buying NO pairs off YES, so it shrinks the position whatever the group holds
+        let s = grouped([60.0, 60.0]);
+        assert!(check(&s, &order("a-no", Side::Buy, 0.50, 10.0)).is_ok());
+    }
+
+    #[test]
+    fn market_notional_defaults_to_a_full_ladder_side() {
+        let mut s = AppState::default();
+        add_market(&mut s, "a", 0.49, 0.51);
+        let config = &s.config.default;
+        let full = config.order_size * config.risk.max_price;
+        assert_eq!(market_limits(&s, "a").max_notional, Some(full));
+
+        s.config.default.max_position_size = Some(50.0);
+        assert_eq!(market_limits(&s, "a").max_notional, Some(50.0));
+        s.config.default.limits.max_notional = Some(20.0);
+        assert_eq!(market_limits(&s, "a").max_notional, Some(20.0));
+    }
+
+    #[test]
+    fn group_breach_trips_and_clears_under_the_resume_fraction() {
+        let mut s = grouped([60.0, 60.0]);
+        review(&mut s);
+        let group = LimitScope::Group(GROUP.to_string());
+        assert!(s.breaches.contains_key(&group));
+        assert_eq!(restriction(&s, "a"), Some(BreachAction::ReduceOnly));
+
+        // 95 shares: under the limit but over 90% of it
+        s.inventory.insert("a".to_string(), 35.0);
+        review(&mut s);
+        assert!(s.breaches.contains_key(&group));
+
+        s.last_mid_bucket.insert("b".to_string(), 50);
+        s.inventory.insert("a".to_string(), 25.0);
+        review(&mut s);
+        assert!(s.breaches.is_empty());
+        assert_eq!(restriction(&s, "a"), None);
+        assert!(!s.last_mid_bucket.contains_key("b"));
+    }
+
+    #[test]
+    fn flatten_breach_unwinds_its_markets_until_it_clears() {
+        let mut s = grouped([60.0, 60.0]);
+        s.config.portfolio.group.action = BreachAction::Flatten;
+        review(&mut s);
+        unwind::review(&mut s);
+        assert_eq!(restriction(&s, "a"), Some(BreachAction::Flatten));
+        assert!(s.unwinds.contains_key("a") && s.unwinds.contains_key("b"));
+
+        s.inventory.insert("a".to_string(), 25.0);
+        review(&mut s);
+        unwind::review(&mut s);
+        assert!(s.unwinds.is_empty());
+    }
+}
@@ -0,0 +1,207 @@
+use crate::modules::auto_merge::auto_merge_task;
+use crate::modules::data::{fetch_current_positions, fetch_market_metadata};
+use crate::modules::logger;
+use crate::modules::pause::PauseReason;
+use crate::modules::provision::provision_task;
+use crate::modules::types::{AppState, BotCommand, CommandSender};
+use polymarket_rs_client::ClobClient;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tokio::task::JoinHandle;
+use tokio::time::Duration;
+
+const RECONCILE_SECS: u64 = 2; // matches the config reload poll
+
+/// One binary market the bot quotes
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MarketSpec {
+    pub condition_id: String,
+    pub yes_token: String,
+    pub no_token: String,
+}
+
+impl MarketSpec {
+    pub fn tokens(&self) -> [&String; 2] {
+        [&self.yes_token, &self.no_token]
+    }
+}
+
+/// Per-market tasks owned by the manager; aborted when the market stops
+struct RunningMarket {
+    spec: MarketSpec,
+    tasks: Vec<JoinHandle<()>>,
+}
+
+// -------------------- Market Manager Task --------------------
+/// Starts and stops markets so the running set matches the config's `[markets.*]` sections
+/// that name a `condition_id` and `no_token` (plus the market pinned by the environment).
+/// Starting a market seeds its inventory and metadata, registers it in `AppState.markets`,
+/// which the feeds and the trading task follow, and spawns its provisioning and auto-merge
+/// tasks. Stopping one cancels its orders and aborts those tasks; its inventory is kept.
+pub async fn market_manager_task(
+    state: Arc<Mutex<AppState>>,
+    cmd_tx: CommandSender,
+    proxy_wallet: String,
+    client: Arc<Mutex<ClobClient>>,
+    paper_mode: bool,
+    pinned: Option<MarketSpec>, // CONDITIONID / ASSETS_IDS from the environment
+) {
+    let mut running: HashMap<String, RunningMarket> = HashMap::new(); // yes_token -> market
+    let mut interval = tokio::time::interval(Duration::from_secs(RECONCILE_SECS));
+    loop {
+        interval.tick().await;
+        let wanted = {
+            let s = state.lock().await;
+            if s.shutting_down {
+                break;
+            }
+            let mut wanted = s.config.market_specs();
+            if let Some(pinned) = &pinned {
+                if !wanted.iter().any(|m| m.yes_token == pinned.yes_token) {
+                    wanted.push(pinned.clone());
+                }
+            }
+            wanted
+        };
+
+        let stale: Vec<String> = running
+            .iter()
+            .filter(|(_, market)| !wanted.contains(&market.spec))
+            .map(|(yes_token, _)| yes_token.clone())
+            .collect();
+        for yes_token in stale {
+            if let Some(market) = running.remove(&yes_token) {
+                for task in &market.tasks {
+                    task.abort();
+                }
+                for id in stop_market(&state, &market.spec).await {
+                    if cmd_tx.send(BotCommand::Cancel(id)).await.is_err() {
+                        return;
+                    }
+                }
+            }
+        }
+
+        for spec in wanted {
+            if running.contains_key(&spec.yes_token) {
+                continue;
+            }
+            start_market(&state, &client, &spec).await;
+            let provision_client = (!paper_mode).then(|| Arc::clone(&client));
+            let tasks = vec![
+                tokio::spawn(provision_task(
+                    Arc::clone(&state),
+                    cmd_tx.clone(),
+                    spec.clone(),
+                    proxy_wallet.clone(),
+                    provision_client,
+                )),
+                tokio::spawn(auto_merge_task(
+                    Arc::clone(&state),
+                    cmd_tx.clone(),
+                    spec.clone(),
+                    proxy_wallet.clone(),
+                )),
+            ];
+            running.insert(spec.yes_token.clone(), RunningMarket { spec, tasks });
+        }
+    }
+}
+
+/// Seed positions and metadata, then register the market so feeds and strategy pick it up
+async fn start_market(
+    state: &Arc<Mutex<AppState>>,
+    client: &Arc<Mutex<ClobClient>>,
+    spec: &MarketSpec,
+) {
+    logger::logln(format!(
+        "Markets: Starting {} (YES {}, NO {})",
+        spec.condition_id, spec.yes_token, spec.no_token
+    ));
+    let tokens = vec![spec.yes_token.clone(), spec.no_token.clone()];
+    let positions = fetch_current_positions(client, &tokens, &spec.condition_id)
+        .await
+        .map_err(|e| e.to_string());
+    let metadata = fetch_market_metadata(&spec.condition_id)
+        .await
+        .map_err(|e| e.to_string());
+
+    let mut s = state.lock().await;
+    s.token_pairs
+        .insert(spec.yes_token.clone(), spec.no_token.clone());
+    s.token_pairs
+        .insert(spec.no_token.clone(), spec.yes_token.clone());
+    for token in &tokens {
+        s.inventory.entry(token.clone()).or_insert(0.0);
+    }
+    match positions {
+        Ok(positions) => {
+            logger::logln(format!("Markets: Current positions: {:?}", positions));
+            s.inventory.extend(positions);
+        }
+        Err(e) => logger::logln(format!(
+            "Markets: Could not fetch positions for {} ({}); keeping persisted inventory",
+            spec.condition_id, e
+        )),
+    }
+    // End date and game start drive the wind-down (config end_date/game_start override them);
+    // reward terms drive the rewards mode; the neg-risk event groups limits
+    match metadata {
+        Ok(metadata) => {
+            logger::logln(format!("Markets: Metadata: {:?}", metadata));
+            s.schedules
+                .insert(spec.yes_token.clone(), metadata.schedule);
+            match metadata.rewards {
+                Some(rewards) => s.reward_params.insert(spec.yes_token.clone(), rewards),
+                None => s.reward_params.remove(&spec.yes_token),
+            };
+            match metadata.event_group {
+                Some(group) => s.event_groups.insert(spec.yes_token.clone(), group),
+                None => s.event_groups.remove(&spec.yes_token),
+            };
+        }
+        Err(e) => logger::logln(format!(
+            "Markets: Could not fetch metadata for {}: {}",
+            spec.condition_id, e
+        )),
+    }
+    s.markets.insert(spec.yes_token.clone(), spec.clone());
+}
+
+/// Unregister the market and return its resting orders to cancel; other markets are untouched
+async fn stop_market(state: &Arc<Mutex<AppState>>, spec: &MarketSpec) -> Vec<String> {
+    let mut s = state.lock().await;
+    s.markets.remove(&spec.yes_token);
+    s.last_mid_bucket.remove(&spec.yes_token);
+    s.pauses
+        .remove(&PauseReason::ExtremePrice(spec.yes_token.clone()));
+    s.pauses
+        .remove(&PauseReason::PriceJump(spec.yes_token.clone()));
+    for token in spec.tokens() {
+        s.reference_mids.remove(token);
+        s.quoted_at.remove(token);
+    }
+    s.pending_creates
+        .retain(|o| !spec.tokens().contains(&&o.asset_id));
+    // A merge or split still in flight settles inventory on its own; a restarted market
+    // must not wait on it
+    s.merge_reserved.remove(&spec.yes_token);
+    s.split_pending.remove(&spec.yes_token);
+    let held: Vec<f64> = spec
+        .tokens()
+        .iter()
+        .map(|t| s.inventory.get(*t).copied().unwrap_or(0.0))
+        .collect();
+    logger::logln(format!(
+        "Markets: Stopping {}; canceling its orders and keeping {:.2} YES / {:.2} NO",
+        spec.condition_id, held[0], held[1]
+    ));
+    s.my_open_orders
+        .iter()
+        .filter(|(id, o)| {
+            spec.tokens().contains(&&o.asset_id) && !s.cancel_pending.contains_key(*id)
+        })
+        .map(|(id, _)| id.clone())
+        .collect()
+}
@@ -0,0 +1,273 @@
+use crate::modules::logger;
+use crate::modules::strategy::Fill;
+use crate::modules::types::{AppState, Side};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::time::Instant;
+
+// -------------------- Markout Defaults --------------------
+pub const HORIZONS_SECS: [u64; 4] = [1, 10, 60, 300];
+const HORIZON_LABELS: [&str; 4] = ["+1s", "+10s", "+60s", "+5m"];
+const DEFAULT_HORIZON_SECS: u64 = 10; // markout that drives the guard
+const DEFAULT_LOOKBACK_SECS: u64 = 900; // fills older than this no longer count as recent
+const DEFAULT_MIN_FILLS: u32 = 3;
+const DEFAULT_THRESHOLD: f64 = 0.005; // act below -0.005 per share
+const DEFAULT_WIDEN_MULTIPLIER: f64 = 2.0;
+const MAX_TRACKED_FILLS: usize = 10_000;
+
+/// What to do with a side whose recent fills mark out badly
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MarkoutAction {
+    /// Record markouts only
+    Off,
+    /// Multiply that side's distance from the market by `widen_multiplier`
+    Widen,
+    /// Stop quoting that side until the bad fills age out of the lookback
+    Pull,
+}
+
+/// `markout` in the config
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MarkoutConfig {
+    pub action: MarkoutAction,
+    pub horizon_secs: u64,  // one of HORIZONS_SECS
+    pub lookback_secs: u64, // fills within this window are "recent"
+    pub min_fills: u32,     // recent fills needed before acting
+    pub threshold: f64,     // act when the average markout per share is below -threshold
+    pub widen_multiplier: f64,
+}
+
+impl Default for MarkoutConfig {
+    fn default() -> Self {
+        Self {
+            action: MarkoutAction::Off,
+            horizon_secs: DEFAULT_HORIZON_SECS,
+            lookback_secs: DEFAULT_LOOKBACK_SECS,
+            min_fills: DEFAULT_MIN_FILLS,
+            threshold: DEFAULT_THRESHOLD,
+            widen_multiplier: DEFAULT_WIDEN_MULTIPLIER,
+        }
+    }
+}
+
+/// How a strategy should treat one side of its quotes
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MarkoutGuard {
+    Normal,
+    Widen(f64),
+    Pull,
+}
+
+impl MarkoutGuard {
+    /// Multiplier for the side's distance from the market
+    pub fn widen(&self) -> f64 {
+        match self {
+            MarkoutGuard::Widen(multiplier) => *multiplier,
+            _ => 1.0,
+        }
+    }
+
+    pub fn pulls(&self) -> bool {
+        *self == MarkoutGuard::Pull
+    }
+}
+
+/// One fill in YES terms: a Buy is our bid, a Sell our ask
+#[derive(Clone, Debug)]
+struct MarkedFill {
+    market: String, // yes_token
+    side: Side,
+    price: f64,
+    size: f64,
+    mid_at_fill: f64,
+    at: Instant,
+    mids: [Option<f64>; 4], // YES mid at each of HORIZONS_SECS
+}
+
+impl MarkedFill {
+    /// Per-share PnL against the mid at horizon `i`; positive means the fill was not toxic
+    fn markout(&self, i: usize) -> Option<f64> {
+        let sign = if self.side == Side::Buy { 1.0 } else { -1.0 };
+        self.mids[i].map(|mid| sign * (mid - self.price))
+    }
+}
+
+/// Size-weighted markouts of one market side across every tracked horizon
+#[derive(Clone, Debug, Default)]
+pub struct MarkoutStats {
+    pub fills: u32,
+    pnl: [f64; 4],  // sum of markout * size
+    size: [f64; 4], // sum of size with the horizon sampled
+}
+
+impl MarkoutStats {
+    /// Average markout per share at horizon `i`, once any fill reached it
+    pub fn average(&self, i: usize) -> Option<f64> {
+        (self.size[i] > 0.0).then(|| self.pnl[i] / self.size[i])
+    }
+}
+
+/// Fills awaiting (or recently done with) their markouts, plus running totals
+#[derive(Clone, Debug, Default)]
+pub struct MarkoutTracker {
+    fills: VecDeque<MarkedFill>,
+    pub stats: HashMap<(String, Side), MarkoutStats>, // (yes_token, side) -> totals
+}
+
+/// Record a fill with the current mid; its markouts are sampled as time passes
+pub fn record_fill(s: &mut AppState, fill: &Fill) {
+    let Some(yes_token) = s.yes_token_of(&fill.asset_id).map(str::to_string) else {
+        return;
+    };
+    let (side, price) = if fill.asset_id == yes_token {
+        (fill.side.clone(), fill.price)
+    } else if s.token_pairs.get(&yes_token) == Some(&fill.asset_id) {
+        let side = match fill.side {
+            Side::Buy => Side::Sell,
+            Side::Sell => Side::Buy,
+        };
+        (side, 1.0 - fill.price)
+    } else {
+        return;
+    };
+    let Some(mid_at_fill) = yes_mid(s, &yes_token) else {
+        return;
+    };
+    let tracker = &mut s.markouts;
+    tracker
+        .stats
+        .entry((yes_token.clone(), side.clone()))
+        .or_default()
+        .fills += 1;
+    if tracker.fills.len() >= MAX_TRACKED_FILLS {
+        tracker.fills.pop_front();
+    }
+    tracker.fills.push_back(MarkedFill {
+        market: yes_token,
+        side,
+        price,
+        size: fill.size,
+        mid_at_fill,
+        at: Instant::now(),
+        mids: [None; 4],
+    });
+}
+
+/// Record the mid for every horizon that has come due. Called before a new price is applied
+/// and on the 1s timer, so a horizon sees the mid that was current when it elapsed.
+pub fn sample(s: &mut AppState) {
+    let now = Instant::now();
+    let mut mids: HashMap<String, Option<f64>> = HashMap::new();
+    for fill in s.markouts.fills.iter_mut() {
+        let age = now.duration_since(fill.at).as_secs_f64();
+        for (i, horizon) in HORIZONS_SECS.iter().enumerate() {
+            if fill.mids[i].is_some() || age < *horizon as f64 {
+                continue;
+            }
+            let mid = *mids
+                .entry(fill.market.clone())
+                .or_insert_with(|| yes_mid_of(&s.last_prices, &s.token_pairs, &fill.market));
+            let Some(mid) = mid else {
+                continue;
+            };
+            fill.mids[i] = Some(mid);
+            let markout = fill.markout(i).unwrap_or(0.0);
+            let stats = s
+                .markouts
+                .stats
+                .entry((fill.market.clone(), fill.side.clone()))
+                .or_default();
+            stats.pnl[i] += markout * fill.size;
+            stats.size[i] += fill.size;
+            if i == HORIZONS_SECS.len() - 1 {
+                let markouts: Vec<String> = (0..HORIZONS_SECS.len())
+                    .map(|j| {
+                        format!(
+                            "{}={:+.4}",
+                            HORIZON_LABELS[j],
+                            fill.markout(j).unwrap_or(0.0)
+                        )
+                    })
+                    .collect();
+                logger::logln(format!(
+                    "Markout: {:?} {:.2} @ {:.4} (mid {:.4}) {}",
+                    fill.side,
+                    fill.size,
+                    fill.price,
+                    fill.mid_at_fill,
+                    markouts.join(" ")
+                ));
+            }
+        }
+    }
+
+    // Forget fills that are fully marked and past every lookback
+    let config = &s.config;
+    let token_pairs = &s.token_pairs;
+    s.markouts.fills.retain(|fill| {
+        let lookback = config
+            .market(&fill.market, token_pairs.get(&fill.market))
+            .markout
+            .lookback_secs
+            .max(*HORIZONS_SECS.last().unwrap_or(&0));
+        fill.mids.iter().any(Option::is_none) || fill.at.elapsed().as_secs() <= lookback
+    });
+}
+
+/// How the strategy should quote `side` (in YES terms) of the market given its recent markouts
+pub fn guard(s: &AppState, yes_token: &str, side: Side) -> MarkoutGuard {
+    let config = &s.market_config(yes_token).markout;
+    if config.action == MarkoutAction::Off {
+        return MarkoutGuard::Normal;
+    }
+    let Some(i) = HORIZONS_SECS.iter().position(|h| *h == config.horizon_secs) else {
+        return MarkoutGuard::Normal;
+    };
+    let (mut fills, mut pnl, mut size) = (0, 0.0, 0.0);
+    for fill in &s.markouts.fills {
+        if fill.market != yes_token
+            || fill.side != side
+            || fill.at.elapsed().as_secs() > config.lookback_secs
+        {
+            continue;
+        }
+        if let Some(markout) = fill.markout(i) {
+            fills += 1;
+            pnl += markout * fill.size;
+            size += fill.size;
+        }
+    }
+    if fills < config.min_fills || size <= 0.0 || pnl / size >= -config.threshold {
+        return MarkoutGuard::Normal;
+    }
+    match config.action {
+        MarkoutAction::Off => MarkoutGuard::Normal,
+        MarkoutAction::Widen => MarkoutGuard::Widen(config.widen_multiplier),
+        MarkoutAction::Pull => MarkoutGuard::Pull,
+    }
+}
+
+pub fn horizon_label(i: usize) -> &'static str {
+    HORIZON_LABELS[i]
+}
+
+pub fn yes_mid(s: &AppState, yes_token: &str) -> Option<f64> {
+    yes_mid_of(&s.last_prices, &s.token_pairs, yes_token)
+}
+
+/// YES mid from the YES book, or from the NO book when only that one is known
+fn yes_mid_of(
+    last_prices: &HashMap<String, (f64, f64, i64)>,
+    token_pairs: &HashMap<String, String>,
+    yes_token: &str,
+) -> Option<f64> {
+    if let Some((bid, ask, _)) = last_prices.get(yes_token) {
+        return Some((bid + ask) / 2.0);
+    }
+    let no_token = token_pairs.get(yes_token)?;
+    last_prices
+        .get(no_token)
+        .map(|(bid, ask, _)| 1.0 - (bid + ask) / 2.0)
+}
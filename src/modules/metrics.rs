@@ -0,0 +1,118 @@
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+const MAX_SAMPLES: usize = 1000; // rolling window per stage
+const EARLY_PLACEMENT_TTL: Duration = Duration::from_secs(60);
+
+/// Measured legs of an order's life, from strategy emission to exchange acknowledgement
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum LatencyStage {
+    /// Command emitted by the strategy -> picked up by the execution task
+    Queue,
+    /// `create_order` (EIP-712 signing, plus tick/neg-risk lookups)
+    Sign,
+    /// `post_order` HTTP round trip
+    Post,
+    /// Command emitted -> `post_order` response
+    RoundTrip,
+    /// Command emitted -> user-channel PLACEMENT event
+    Placement,
+    /// `cancel` HTTP round trip
+    Cancel,
+    /// `cancel_all` HTTP round trip
+    CancelAll,
+}
+
+impl LatencyStage {
+    pub const ALL: [LatencyStage; 7] = [
+        LatencyStage::Queue,
+        LatencyStage::Sign,
+        LatencyStage::Post,
+        LatencyStage::RoundTrip,
+        LatencyStage::Placement,
+        LatencyStage::Cancel,
+        LatencyStage::CancelAll,
+    ];
+}
+
+/// p50/p90/p99/max in milliseconds over the rolling window
+#[derive(Clone, Copy, Debug)]
+pub struct Percentiles {
+    pub count: usize,
+    pub p50: f64,
+    pub p90: f64,
+    pub p99: f64,
+    pub max: f64,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct LatencyStats {
+    samples: HashMap<LatencyStage, VecDeque<Duration>>,
+    pending_acks: HashMap<String, Instant>, // order_id -> command emitted_at
+    early_placements: HashMap<String, Instant>, // PLACEMENT seen before post_order returned
+}
+
+impl LatencyStats {
+    pub fn record(&mut self, stage: LatencyStage, elapsed: Duration) {
+        let window = self.samples.entry(stage).or_default();
+        if window.len() == MAX_SAMPLES {
+            window.pop_front();
+        }
+        window.push_back(elapsed);
+    }
+
+    pub fn percentiles(&self, stage: LatencyStage) -> Option<Percentiles> {
+        let window = self.samples.get(&stage)?;
+        if window.is_empty() {
+            return None;
+        }
+        let mut sorted: Vec<f64> = window.iter().map(|d| d.as_secs_f64() * 1000.0).collect();
+        sorted.sort_by(|a, b| a.total_cmp(b));
+        let pick = |q: f64| sorted[((sorted.len() - 1) as f64 * q).round() as usize];
+        Some(Percentiles {
+            count: sorted.len(),
+            p50: pick(0.50),
+            p90: pick(0.90),
+            p99: pick(0.99),
+            max: sorted[sorted.len() - 1],
+        })
+    }
+
+    /// Called once the order id is known; waits for the matching PLACEMENT event
+    pub fn expect_placement(&mut self, order_id: &str, emitted_at: Instant) {
+        match self.early_placements.remove(order_id) {
+            Some(seen_at) => {
+                self.record(
+                    LatencyStage::Placement,
+                    seen_at.saturating_duration_since(emitted_at),
+                );
+            }
+            None => {
+                self.pending_acks.insert(order_id.to_string(), emitted_at);
+            }
+        }
+    }
+
+    /// Called from the user channel on PLACEMENT
+    pub fn on_placement(&mut self, order_id: &str) {
+        let now = Instant::now();
+        match self.pending_acks.remove(order_id) {
+            Some(emitted_at) => self.record(LatencyStage::Placement, now - emitted_at),
+            None => {
+                self.early_placements
+                    .retain(|_, seen| now.duration_since(*seen) < EARLY_PLACEMENT_TTL);
+                self.early_placements.insert(order_id.to_string(), now);
+            }
+        }
+    }
+
+    /// Orders canceled or filled before their PLACEMENT arrived will never be acked
+    pub fn forget(&mut self, order_id: &str) {
+        self.pending_acks.remove(order_id);
+    }
+
+    /// Same for every order after a confirmed CancelAll
+    pub fn forget_all(&mut self) {
+        self.pending_acks.clear();
+    }
+}
@@ -1,10 +1,38 @@
+pub mod auto_merge;
+pub mod avellaneda;
+pub mod circuit_breaker;
+pub mod config;
 pub mod data;
+pub mod edge_strategy;
 pub mod execution;
+pub mod heartbeat;
+pub mod ladder;
+pub mod limits;
 pub mod logger;
+pub mod market_manager;
+pub mod markout;
+pub mod metrics;
 pub mod monitor;
+pub mod order_book;
+pub mod paper;
+pub mod pause;
 pub mod persistence;
+pub mod provision;
+pub mod queue;
+pub mod rate_limit;
+pub mod requote;
+pub mod retry;
+pub mod rewards;
+pub mod risk;
+pub mod skew;
 pub mod split_merge;
+pub mod strategy;
+#[cfg(test)]
+pub mod test_support;
 pub mod trading;
 pub mod types;
+pub mod unwind;
 pub mod user_ws;
+pub mod volatility;
 pub mod websocket;
+pub mod wind_down;
@@ -1,4 +1,12 @@
+use crate::modules::limits::{self, LimitScope, LimitSet};
+use crate::modules::markout::{self, HORIZONS_SECS};
+use crate::modules::metrics::LatencyStage;
+use crate::modules::queue;
+use crate::modules::rewards;
+use crate::modules::skew::dollar_imbalance;
+use crate::modules::strategy::YesBook;
 use crate::modules::types::{AppState, Order, Side};
+use crate::modules::wind_down;
 use std::sync::Arc;
 use tokio::sync::Mutex;
 use tokio::time::Duration;
@@ -19,23 +27,44 @@ pub async fn monitor_task(state: Arc<Mutex<AppState>>) {
         // Display current state
         println!("\n=== BOT STATUS ===");
         println!("Risk Paused: {}", s.risk_paused);
+        for (reason, active) in &s.pauses {
+            match active.resume_since {
+                Some(since) => println!(
+                    "  {:?}: recovering, {}s of {}s cool-down",
+                    reason,
+                    since.elapsed().as_secs(),
+                    active.cooldown_secs
+                ),
+                None => println!(
+                    "  {:?}: paused for {}s",
+                    reason,
+                    active.since.elapsed().as_secs()
+                ),
+            }
+        }
         println!("Shutting Down: {}", s.shutting_down);
         println!("Total Open Orders: {}", s.my_open_orders.len());
+        if s.quoting_blocked() {
+            println!(
+                "Quoting Blocked: {} unconfirmed cancels{}",
+                s.cancel_pending.len(),
+                if s.cancel_all_pending {
+                    " (cancel-all pending)"
+                } else {
+                    ""
+                }
+            );
+        }
 
         // Show inventory with dollar values
         if !s.inventory.is_empty() {
             println!("\n--- Inventory ---");
-            let mut total_dollar_value = 0.0;
-            let mut inventory_dollars = Vec::new();
-
             for (token_id, quantity) in &s.inventory {
                 if *quantity != 0.0 {
                     // Get current price for this token
                     if let Some((bid, ask, _)) = s.last_prices.get(token_id) {
                         let current_price = (bid + ask) / 2.0;
                         let dollar_value = quantity * current_price;
-                        total_dollar_value += dollar_value.abs();
-                        inventory_dollars.push(dollar_value);
                         println!(
                             "Token {}: {:.2} tokens (${:.2})",
                             token_id, quantity, dollar_value
@@ -45,17 +74,84 @@ pub async fn monitor_task(state: Arc<Mutex<AppState>>) {
                     }
                 }
             }
+        }
 
-            // Calculate inventory imbalance in dollars
-            if inventory_dollars.len() == 2 {
-                let imbalance_dollars = (inventory_dollars[0] - inventory_dollars[1]).abs();
+        // Per-market imbalance and the shared portfolio budget
+        let mut markets: Vec<_> = s.markets.values().collect();
+        markets.sort_by(|a, b| a.yes_token.cmp(&b.yes_token));
+        if !markets.is_empty() {
+            println!("\n--- Markets ---");
+            for market in &markets {
+                let config = s.market_config(&market.yes_token);
+                let book = s
+                    .last_prices
+                    .get(&market.yes_token)
+                    .map(|(bid, ask, _)| YesBook {
+                        yes_token: market.yes_token.clone(),
+                        no_token: market.no_token.clone(),
+                        best_bid: *bid,
+                        best_ask: *ask,
+                    });
+                let orders = s
+                    .my_open_orders
+                    .values()
+                    .filter(|o| market.tokens().contains(&&o.asset_id))
+                    .count();
+                match book {
+                    Some(book) => println!(
+                        "Market {} ({}): {} orders, imbalance ${:.2} (max: ${:.2}){}",
+                        market.condition_id,
+                        config.strategy,
+                        orders,
+                        dollar_imbalance(&book, &s),
+                        config.max_inventory_imbalance,
+                        if config.enabled { "" } else { " [disabled]" }
+                    ),
+                    None => println!(
+                        "Market {} ({}): {} orders, no prices yet",
+                        market.condition_id, config.strategy, orders
+                    ),
+                }
+            }
+
+            // Market, group and account layers, each listed once
+            let scopes: std::collections::BTreeMap<LimitScope, LimitSet> = markets
+                .iter()
+                .flat_map(|market| limits::scopes_of(&s, &market.yes_token))
+                .collect();
+            println!("\n--- Risk Limits ---");
+            for (scope, limit_set) in &scopes {
+                let used = limits::usage(&s, scope);
+                let breach = s.breaches.get(scope).map_or(String::new(), |b| {
+                    format!(" [{:?}: {}]", b.action, b.reason)
+                });
                 println!(
-                    "Inventory Imbalance: ${:.2} (max: ${:.2})",
-                    imbalance_dollars, s.max_inventory_imbalance
+                    "{}: unpaired ${:.2} / {:.2} shares, open ${:.2}, daily PnL ${:.2} (max: {} / {} shares, open {}, loss {}){}",
+                    scope,
+                    used.notional,
+                    used.shares,
+                    used.open_notional,
+                    used.daily_pnl,
+                    limit_str(limit_set.max_notional),
+                    limit_str(limit_set.max_shares),
+                    limit_str(limit_set.max_open_notional),
+                    limit_str(limit_set.max_daily_loss),
+                    breach
                 );
+            }
+        }
+
+        let mut unwinds: Vec<_> = s.unwinds.iter().collect();
+        unwinds.sort_by(|a, b| a.0.cmp(b.0));
+        if !unwinds.is_empty() {
+            println!("\n--- Unwind ---");
+            for (yes_token, unwind) in unwinds {
                 println!(
-                    "Total Position: ${:.2} (max: ${:.2})",
-                    total_dollar_value, s.max_position_size
+                    "Market {}: {:?} for {}s, started with {:.2} unpaired shares",
+                    yes_token,
+                    unwind.phase,
+                    unwind.started.elapsed().as_secs(),
+                    unwind.start_shares
                 );
             }
         }
@@ -95,13 +191,25 @@ pub async fn monitor_task(state: Arc<Mutex<AppState>>) {
                         Side::Buy => "BUY",
                         Side::Sell => "SELL",
                     };
+                    let queue = order
+                        .id
+                        .as_deref()
+                        .and_then(|id| s.queue_ahead.get(id).map(|ahead| (id, ahead)))
+                        .map_or(String::new(), |(id, ahead)| {
+                            format!(
+                                ", ~{:.0} ahead ({:.0}% of level)",
+                                ahead,
+                                queue::ahead_fraction(&s, id, order).unwrap_or(0.0) * 100.0
+                            )
+                        });
                     println!(
-                        "  {}: {} {} @ {:.4} (size: {:.2})",
+                        "  {}: {} {} @ {:.4} (size: {:.2}{})",
                         i + 1,
                         side_str,
                         order.id.as_deref().unwrap_or("pending"),
                         order.price,
-                        order.size
+                        order.size,
+                        queue
                     );
                 }
                 if orders.len() > 3 {
@@ -123,6 +231,105 @@ pub async fn monitor_task(state: Arc<Mutex<AppState>>) {
             }
         }
 
+        // Show time to resolution / game start and the wind-down it implies
+        let scheduled: Vec<_> = markets
+            .iter()
+            .filter_map(|m| wind_down::cutoff(&s, &m.yes_token).map(|cutoff| (m, cutoff)))
+            .collect();
+        if !scheduled.is_empty() {
+            println!("\n--- Schedule ---");
+            for (market, cutoff) in scheduled {
+                let yes_token = &market.yes_token;
+                println!(
+                    "Market {}: resolution {:?}, cutoff: {} ({} min left), wind-down: {:?}",
+                    market.condition_id,
+                    wind_down::end_date(&s, yes_token),
+                    cutoff,
+                    (cutoff - chrono::Utc::now()).num_minutes(),
+                    wind_down::phase(&s, yes_token)
+                );
+            }
+        }
+
+        // Show our expected share of liquidity rewards
+        let estimates: Vec<_> = markets
+            .iter()
+            .filter_map(|m| rewards::estimate(&s, &m.yes_token).map(|e| (m, e)))
+            .collect();
+        if !estimates.is_empty() {
+            println!("\n--- Liquidity Rewards ---");
+            for (market, estimate) in estimates {
+                let yes_token = &market.yes_token;
+                println!(
+                    "Market {}: ours={:.2} total={:.2} share={:.2}% expected=${:.2}/day (mode {})",
+                    market.condition_id,
+                    estimate.our_score,
+                    estimate.total_score,
+                    estimate.share * 100.0,
+                    estimate.daily,
+                    if s.market_config(yes_token).rewards.enabled {
+                        "on"
+                    } else {
+                        "off"
+                    }
+                );
+            }
+        }
+
+        // Show realized volatility and the spread scale it implies
+        if !s.volatility.is_empty() {
+            println!("\n--- Volatility (per sqrt(min)) ---");
+            for (token_id, estimator) in &s.volatility {
+                let cfg = &s.market_config(token_id).volatility;
+                match estimator.sigmas(cfg) {
+                    Some((fast, slow)) => println!(
+                        "Token {}: fast={:.4} slow={:.4} spread x{:.2}",
+                        token_id,
+                        fast,
+                        slow,
+                        estimator.spread_multiplier(cfg)
+                    ),
+                    None => println!("Token {}: warming up", token_id),
+                }
+            }
+        }
+
+        // Show average fill markouts per market side
+        if !s.markouts.stats.is_empty() {
+            println!("\n--- Markouts (per share) ---");
+            for ((market, side), stats) in &s.markouts.stats {
+                let horizons: Vec<String> = (0..HORIZONS_SECS.len())
+                    .map(|i| match stats.average(i) {
+                        Some(avg) => format!("{}={:+.4}", markout::horizon_label(i), avg),
+                        None => format!("{}=-", markout::horizon_label(i)),
+                    })
+                    .collect();
+                println!(
+                    "Market {} {:?}: fills={} {} guard={:?}",
+                    market,
+                    side,
+                    stats.fills,
+                    horizons.join(" "),
+                    markout::guard(&s, market, side.clone())
+                );
+            }
+        }
+
+        // Show latency percentiles per endpoint/stage
+        let latency: Vec<(LatencyStage, _)> = LatencyStage::ALL
+            .iter()
+            .filter_map(|stage| s.latency.percentiles(*stage).map(|p| (*stage, p)))
+            .collect();
+        if !latency.is_empty() {
+            println!("\n--- Latency (ms) ---");
+            for (stage, p) in latency {
+                println!(
+                    "{:?}: p50={:.1} p90={:.1} p99={:.1} max={:.1} (n={})",
+                    stage, p.p50, p.p90, p.p99, p.max, p.count
+                );
+            }
+        }
+
         println!("==================\n");
     }
 }
@@ -131,3 +338,7 @@ pub async fn monitor_task(state: Arc<Mutex<AppState>>) {
 async fn is_shutting_down(state: &Arc<Mutex<AppState>>) -> bool {
     state.lock().await.shutting_down
 }
+
+fn limit_str(max: Option<f64>) -> String {
+    max.map_or("-".to_string(), |max| format!("{:.2}", max))
+}
@@ -0,0 +1,88 @@
+use crate::modules::types::Side;
+use std::collections::BTreeMap;
+
+const PRICE_SCALE: f64 = 10_000.0; // levels are keyed by price in 1/10000ths
+
+/// Aggregated size per price level of one token's book, from `book` snapshots and
+/// `price_change` deltas on the market WS
+#[derive(Clone, Debug, Default)]
+pub struct DepthBook {
+    bids: BTreeMap<i64, f64>,
+    asks: BTreeMap<i64, f64>,
+}
+
+/// A change to one asset's depth
+#[derive(Clone, Debug)]
+pub enum DepthUpdate {
+    Snapshot {
+        asset_id: String,
+        bids: Vec<(f64, f64)>, // (price, size)
+        asks: Vec<(f64, f64)>,
+    },
+    Level {
+        asset_id: String,
+        side: Side,
+        price: f64,
+        size: f64, // new total at the level; 0 removes it
+    },
+}
+
+impl DepthUpdate {
+    pub fn asset_id(&self) -> &str {
+        match self {
+            DepthUpdate::Snapshot { asset_id, .. } | DepthUpdate::Level { asset_id, .. } => {
+                asset_id
+            }
+        }
+    }
+}
+
+impl DepthBook {
+    pub fn apply(&mut self, update: &DepthUpdate) {
+        match update {
+            DepthUpdate::Snapshot { bids, asks, .. } => {
+                self.bids = bids.iter().map(|(p, s)| (price_key(*p), *s)).collect();
+                self.asks = asks.iter().map(|(p, s)| (price_key(*p), *s)).collect();
+            }
+            DepthUpdate::Level {
+                side, price, size, ..
+            } => {
+                let levels = self.side_mut(side);
+                if *size > 0.0 {
+                    levels.insert(price_key(*price), *size);
+                } else {
+                    levels.remove(&price_key(*price));
+                }
+            }
+        }
+    }
+
+    /// (price, size) levels of one side, best first
+    pub fn levels(&self, side: &Side) -> Vec<(f64, f64)> {
+        let to_level = |(k, s): (&i64, &f64)| (*k as f64 / PRICE_SCALE, *s);
+        match side {
+            Side::Buy => self.bids.iter().rev().map(to_level).collect(),
+            Side::Sell => self.asks.iter().map(to_level).collect(),
+        }
+    }
+
+    /// Size resting at exactly `price` on one side
+    pub fn size_at(&self, side: &Side, price: f64) -> f64 {
+        let levels = match side {
+            Side::Buy => &self.bids,
+            Side::Sell => &self.asks,
+        };
+        levels.get(&price_key(price)).copied().unwrap_or(0.0)
+    }
+
+    fn side_mut(&mut self, side: &Side) -> &mut BTreeMap<i64, f64> {
+        match side {
+            Side::Buy => &mut self.bids,
+            Side::Sell => &mut self.asks,
+        }
+    }
+}
+
+fn price_key(price: f64) -> i64 {
+    (price * PRICE_SCALE).round() as i64
+}
@@ -0,0 +1,170 @@
+use crate::modules::execution::{ExecResult, ExecutionBackend, Placement};
+use crate::modules::logger;
+use crate::modules::strategy::{Fill, StrategyEvent};
+use crate::modules::types::{AppState, BotCommand, CommandSender, Order, OrderId, Side};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::sync::{mpsc, Mutex};
+use tokio::time::Duration;
+
+const MATCH_INTERVAL_MS: u64 = 250; // How often resting paper orders are checked against the book
+
+/// Resting paper orders shared between the backend and the matching task
+pub type PaperBook = Arc<Mutex<HashMap<OrderId, Order>>>;
+
+// -------------------- Paper Backend --------------------
+/// Simulated venue: orders rest locally and are filled by `paper_matching_task`
+/// when the live book trades through them. Nothing is signed or sent on-chain.
+pub struct PaperBackend {
+    book: PaperBook,
+    next_id: AtomicU64,
+}
+
+impl Default for PaperBackend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PaperBackend {
+    pub fn new() -> Self {
+        Self {
+            book: Arc::new(Mutex::new(HashMap::new())),
+            next_id: AtomicU64::new(1),
+        }
+    }
+
+    /// Handle for the matching task
+    pub fn book(&self) -> PaperBook {
+        Arc::clone(&self.book)
+    }
+}
+
+impl ExecutionBackend for PaperBackend {
+    fn name(&self) -> &'static str {
+        "paper"
+    }
+
+    fn reports_placements(&self) -> bool {
+        false
+    }
+
+    async fn place_order(&self, order: &Order, _expiration: Option<u64>) -> ExecResult<Placement> {
+        let order_id = format!("paper-{}", self.next_id.fetch_add(1, Ordering::Relaxed));
+        let mut resting = order.clone();
+        resting.id = Some(order_id.clone());
+        self.book.lock().await.insert(order_id.clone(), resting);
+        logger::logln(format!(
+            "Paper: Resting {:?} {} @ {:.4} x {:.2} as {}",
+            order.side, order.asset_id, order.price, order.size, order_id
+        ));
+        Ok(Placement {
+            order_id,
+            sign: None,
+            post: None,
+        })
+    }
+
+    async fn cancel(&self, order_id: &str) -> ExecResult<()> {
+        self.book.lock().await.remove(order_id);
+        Ok(())
+    }
+
+    async fn cancel_all(&self) -> ExecResult<()> {
+        self.book.lock().await.clear();
+        Ok(())
+    }
+
+    async fn order_is_live(&self, order_id: &str) -> ExecResult<bool> {
+        Ok(self.book.lock().await.contains_key(order_id))
+    }
+
+    async fn merge(&self, shares: i64, condition_id: &str, _proxy_wallet: &str) -> ExecResult<()> {
+        logger::logln(format!(
+            "Paper: Simulated merge of {} shares for condition {}",
+            shares, condition_id
+        ));
+        Ok(())
+    }
+
+    async fn split(&self, shares: i64, condition_id: &str, _proxy_wallet: &str) -> ExecResult<()> {
+        logger::logln(format!(
+            "Paper: Simulated split of {} USDC for condition {}",
+            shares, condition_id
+        ));
+        Ok(())
+    }
+}
+
+// -------------------- Paper Matching Task --------------------
+/// Fill resting paper orders when the live top of book trades through them.
+/// A BUY fills once best ask <= our price, a SELL once best bid >= our price; fills are
+/// at our limit price and go through `BotCommand::OrderFilled` like any other fill, and
+/// reach the strategy as a `StrategyEvent::Fill`.
+pub async fn paper_matching_task(
+    book: PaperBook,
+    state: Arc<Mutex<AppState>>,
+    cmd_tx: CommandSender,
+    event_tx: mpsc::Sender<StrategyEvent>,
+) {
+    let mut interval = tokio::time::interval(Duration::from_millis(MATCH_INTERVAL_MS));
+    loop {
+        interval.tick().await;
+
+        let prices = {
+            let s = state.lock().await;
+            if s.shutting_down {
+                break;
+            }
+            s.last_prices.clone()
+        };
+
+        let fills: Vec<Order> = {
+            let mut resting = book.lock().await;
+            let filled_ids: Vec<OrderId> = resting
+                .iter()
+                .filter(|(_, order)| {
+                    prices
+                        .get(&order.asset_id)
+                        .map(|(bid, ask, _)| match order.side {
+                            Side::Buy => *ask <= order.price,
+                            Side::Sell => *bid >= order.price,
+                        })
+                        .unwrap_or(false)
+                })
+                .map(|(id, _)| id.clone())
+                .collect();
+            filled_ids
+                .iter()
+                .filter_map(|id| resting.remove(id))
+                .collect()
+        };
+
+        for order in fills {
+            let order_id = order.id.clone().unwrap_or_default();
+            logger::logln(format!(
+                "Paper: Filled {} {:?} {} @ {:.4} x {:.2}",
+                order_id, order.side, order.asset_id, order.price, order.size
+            ));
+            let fill = Fill {
+                order_id: order_id.clone(),
+                asset_id: order.asset_id.clone(),
+                side: order.side.clone(),
+                price: order.price,
+                size: order.size,
+            };
+            let filled = BotCommand::OrderFilled(
+                order_id,
+                order.asset_id,
+                order.side,
+                order.price,
+                order.size,
+            );
+            if cmd_tx.send(filled).await.is_err() {
+                return;
+            }
+            let _ = event_tx.send(StrategyEvent::Fill(fill)).await;
+        }
+    }
+}
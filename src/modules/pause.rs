@@ -0,0 +1,193 @@
+use crate::modules::logger;
+use crate::modules::types::AppState;
+use serde::{Deserialize, Serialize};
+use std::time::Instant;
+
+// -------------------- Pause Defaults --------------------
+const DEFAULT_EXTREME_LOW: f64 = 0.02; // pause while a best bid/ask is at or beyond these
+const DEFAULT_EXTREME_HIGH: f64 = 0.98;
+const DEFAULT_RESUME_LOW: f64 = 0.05; // resume only once prices are back inside these
+const DEFAULT_RESUME_HIGH: f64 = 0.95;
+const DEFAULT_EXTREME_COOLDOWN_SECS: u64 = 60;
+
+/// `pause` in the config
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PauseConfig {
+    pub extreme_low: f64,
+    pub extreme_high: f64,
+    pub resume_low: f64, // hysteresis band: narrower than the extreme one
+    pub resume_high: f64,
+    pub extreme_cooldown_secs: u64, // prices must stay inside the resume band this long
+}
+
+impl Default for PauseConfig {
+    fn default() -> Self {
+        Self {
+            extreme_low: DEFAULT_EXTREME_LOW,
+            extreme_high: DEFAULT_EXTREME_HIGH,
+            resume_low: DEFAULT_RESUME_LOW,
+            resume_high: DEFAULT_RESUME_HIGH,
+            extreme_cooldown_secs: DEFAULT_EXTREME_COOLDOWN_SECS,
+        }
+    }
+}
+
+impl PauseConfig {
+    pub fn is_extreme(&self, best_bid: f64, best_ask: f64) -> bool {
+        [best_bid, best_ask]
+            .iter()
+            .any(|p| *p <= self.extreme_low || *p >= self.extreme_high)
+    }
+
+    fn in_resume_band(&self, best_bid: f64, best_ask: f64) -> bool {
+        [best_bid, best_ask]
+            .iter()
+            .all(|p| *p >= self.resume_low && *p <= self.resume_high)
+    }
+}
+
+/// Why quoting is paused. Each reason has its own resume condition and cool-down.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum PauseReason {
+    /// A best bid/ask of one market (its YES token) reached the extreme band; only that
+    /// market pauses, and resumes once its books are back inside the resume band for
+    /// `extreme_cooldown_secs`
+    ExtremePrice(String),
+    /// One market's mid jumped more than the circuit breaker allows; resumes once no further
+    /// jump has been seen for `circuit_breaker.cooldown_secs`
+    PriceJump(String),
+    /// A task stopped beating; pauses every market and resumes once all components are
+    /// healthy for the heartbeat's cool-down
+    DeadMan,
+}
+
+impl PauseReason {
+    /// The market a reason is limited to; None pauses every market
+    fn market(&self) -> Option<&str> {
+        match self {
+            PauseReason::ExtremePrice(yes_token) | PauseReason::PriceJump(yes_token) => {
+                Some(yes_token)
+            }
+            PauseReason::DeadMan => None,
+        }
+    }
+}
+
+/// An active pause and, while its resume condition holds, since when
+#[derive(Clone, Debug)]
+pub struct Pause {
+    pub since: Instant,
+    pub cooldown_secs: u64,
+    pub resume_since: Option<Instant>,
+}
+
+/// Pause quoting for `reason`. Re-pausing an active reason restarts its cool-down.
+pub fn pause(s: &mut AppState, reason: PauseReason, cooldown_secs: u64, detail: &str) {
+    let now = Instant::now();
+    match s.pauses.get_mut(&reason) {
+        Some(active) => {
+            active.resume_since = None;
+            active.cooldown_secs = cooldown_secs;
+        }
+        None => {
+            logger::logln(format!(
+                "Risk: Paused ({:?}): {}. Resumes after {}s of recovery.",
+                reason, detail, cooldown_secs
+            ));
+            s.pauses.insert(
+                reason.clone(),
+                Pause {
+                    since: now,
+                    cooldown_secs,
+                    resume_since: None,
+                },
+            );
+        }
+    }
+    if reason.market().is_none() {
+        s.risk_paused = true;
+    }
+}
+
+/// Whether quoting `yes_token`'s market is paused, by a reason of its own or a global one
+pub fn is_paused(s: &AppState, yes_token: &str) -> bool {
+    s.risk_paused
+        || s.pauses
+            .keys()
+            .any(|reason| reason.market() == Some(yes_token))
+}
+
+/// Report whether the resume condition of `reason` currently holds. The pause is lifted
+/// once it has held for the full cool-down; any lapse restarts the clock.
+pub fn observe_recovery(s: &mut AppState, reason: PauseReason, recovered: bool) {
+    let Some(active) = s.pauses.get_mut(&reason) else {
+        return;
+    };
+    if !recovered {
+        if active.resume_since.take().is_some() {
+            logger::logln(format!(
+                "Risk: {:?} recovery interrupted; cool-down restarts",
+                reason
+            ));
+        }
+        return;
+    }
+    let now = Instant::now();
+    let resume_since = *active.resume_since.get_or_insert(now);
+    if now.duration_since(resume_since).as_secs() < active.cooldown_secs {
+        return;
+    }
+    let paused_for = now.duration_since(active.since).as_secs();
+    s.pauses.remove(&reason);
+    logger::logln(format!(
+        "Risk: Resumed from {:?} after {}s paused",
+        reason, paused_for
+    ));
+    // Nothing is resting any more; re-quote from scratch
+    if let Some(yes_token) = reason.market() {
+        s.last_mid_bucket.remove(yes_token);
+    }
+    if s.risk_paused && s.pauses.keys().all(|r| r.market().is_some()) {
+        s.risk_paused = false;
+        s.last_mid_bucket.clear();
+    }
+    if s.pauses.is_empty() {
+        logger::logln("Risk: No pause reasons left. Quoting resumed.".to_string());
+    }
+}
+
+/// Evaluate the market-driven resume conditions; called once a second by the trading task
+pub fn review(s: &mut AppState) {
+    let extreme: Vec<String> = s
+        .pauses
+        .keys()
+        .filter_map(|reason| match reason {
+            PauseReason::ExtremePrice(yes_token) => Some(yes_token.clone()),
+            _ => None,
+        })
+        .collect();
+    for yes_token in extreme {
+        let band = &s.market_config(&yes_token).pause;
+        let no_token = s.token_pairs.get(&yes_token);
+        let recovered = [Some(&yes_token), no_token]
+            .into_iter()
+            .flatten()
+            .filter_map(|token| s.last_prices.get(token))
+            .all(|(bid, ask, _)| band.in_resume_band(*bid, *ask));
+        observe_recovery(s, PauseReason::ExtremePrice(yes_token), recovered);
+    }
+
+    // A jump re-pauses and restarts the clock, so recovery only needs the time to pass
+    let jumped: Vec<String> = s
+        .pauses
+        .keys()
+        .filter_map(|reason| match reason {
+            PauseReason::PriceJump(yes_token) => Some(yes_token.clone()),
+            _ => None,
+        })
+        .collect();
+    for yes_token in jumped {
+        observe_recovery(s, PauseReason::PriceJump(yes_token), true);
+    }
+}
@@ -0,0 +1,132 @@
+use crate::modules::config::MarketConfig;
+use crate::modules::data::fetch_current_positions;
+use crate::modules::logger;
+use crate::modules::market_manager::MarketSpec;
+use crate::modules::types::{AppState, BotCommand, CommandSender};
+use polymarket_rs_client::ClobClient;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tokio::time::Duration;
+
+// -------------------- Provisioning Defaults --------------------
+const DEFAULT_MIN_SPLIT: f64 = 10.0; // don't pay gas for smaller splits
+const DEFAULT_INTERVAL_SECS: u64 = 30;
+
+/// `provision` in the config
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProvisionConfig {
+    pub enabled: bool,
+    pub target_shares: Option<f64>, // of each token; unset = enough for one full ladder of sells
+    pub min_split: f64,
+    pub interval_secs: u64,
+}
+
+impl Default for ProvisionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            target_shares: None,
+            min_split: DEFAULT_MIN_SPLIT,
+            interval_secs: DEFAULT_INTERVAL_SECS,
+        }
+    }
+}
+
+/// Shares of each outcome token the market should hold; 0 when provisioning is off
+pub fn target_shares(config: &MarketConfig) -> f64 {
+    if !config.provision.enabled {
+        return 0.0;
+    }
+    config
+        .provision
+        .target_shares
+        .unwrap_or_else(|| config.ladder.side_shares(config.order_size))
+}
+
+/// Whole USDC to split so the scarcer token reaches the target
+fn shortfall(target: f64, yes_held: f64, no_held: f64) -> f64 {
+    (target - yes_held.min(no_held)).max(0.0).ceil()
+}
+
+// -------------------- Provisioning Task --------------------
+/// Keeps enough of one market's YES and NO on hand to post sell-side quotes. At start and every
+/// `interval_secs` it splits only the shortfall against the target, skipping splits below
+/// `min_split`. In live mode on-chain balances are checked first, and a split is skipped
+/// (and inventory synced) when they already cover the target.
+pub async fn provision_task(
+    state: Arc<Mutex<AppState>>,
+    cmd_tx: CommandSender,
+    market: MarketSpec,
+    proxy_wallet: String,
+    client: Option<Arc<Mutex<ClobClient>>>, // None in paper mode: nothing on-chain to check
+) {
+    let tokens = vec![market.yes_token.clone(), market.no_token.clone()];
+    loop {
+        let (target, mut shares, config) = {
+            let s = state.lock().await;
+            if s.shutting_down {
+                break;
+            }
+            let config = s.market_config(&market.yes_token).clone();
+            let held = |token: &str| s.inventory.get(token).copied().unwrap_or(0.0);
+            let target = target_shares(&config);
+            let shares = shortfall(target, held(&market.yes_token), held(&market.no_token));
+            let busy = s.split_pending.contains_key(&market.yes_token)
+                || s.merge_reserved(&market.yes_token) > 0.0;
+            let shares = if busy { 0.0 } else { shares };
+            (target, shares, config.provision)
+        };
+
+        if shares >= config.min_split.max(1.0) {
+            if let Some(client) = &client {
+                let positions = fetch_current_positions(client, &tokens, &market.condition_id)
+                    .await
+                    .map_err(|e| e.to_string());
+                match positions {
+                    Ok(positions) => {
+                        let on_chain =
+                            |token: &String| positions.get(token).copied().unwrap_or(0.0);
+                        shares = shortfall(target, on_chain(&tokens[0]), on_chain(&tokens[1]));
+                        if shares < config.min_split.max(1.0) {
+                            logger::logln(format!(
+                                "Provision: On-chain balances {:?} cover the {:.2} target; skipping split",
+                                positions, target
+                            ));
+                            let mut s = state.lock().await;
+                            for (token, quantity) in positions {
+                                s.inventory.insert(token, quantity);
+                            }
+                        }
+                    }
+                    Err(e) => {
+                        logger::logln(format!(
+                            "Provision: Could not check on-chain balances ({}); skipping split",
+                            e
+                        ));
+                        shares = 0.0;
+                    }
+                }
+            }
+        }
+
+        if shares >= config.min_split.max(1.0) {
+            state
+                .lock()
+                .await
+                .split_pending
+                .insert(market.yes_token.clone(), shares);
+            logger::logln(format!(
+                "Provision: Splitting {} USDC to reach {:.2} of each token in {}",
+                shares, target, market.condition_id
+            ));
+            let cmd = BotCommand::Split(market.clone(), proxy_wallet.clone(), shares as i64);
+            if cmd_tx.send(cmd).await.is_err() {
+                return;
+            }
+        }
+
+        tokio::time::sleep(Duration::from_secs(config.interval_secs)).await;
+    }
+}
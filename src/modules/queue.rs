@@ -0,0 +1,208 @@
+use crate::modules::order_book::DepthUpdate;
+use crate::modules::types::{AppState, Order, Side};
+use serde::{Deserialize, Serialize};
+
+// -------------------- Queue Defaults --------------------
+const DEFAULT_KEEP_WITHIN_TICKS: u32 = 1;
+const DEFAULT_MAX_AHEAD_FRACTION: f64 = 0.5;
+
+/// `queue` in the config: keeping resting orders for their place in the price-time queue
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct QueueConfig {
+    pub keep_within_ticks: u32, // a replacement this close in price keeps a well-placed order; 0 = off
+    pub max_ahead_fraction: f64, // well placed: at most this share of the level's other size is ahead
+}
+
+impl Default for QueueConfig {
+    fn default() -> Self {
+        Self {
+            keep_within_ticks: DEFAULT_KEEP_WITHIN_TICKS,
+            max_ahead_fraction: DEFAULT_MAX_AHEAD_FRACTION,
+        }
+    }
+}
+
+// -------------------- Queue Estimates --------------------
+/// A placed order joins the back of its level: everything resting there is ahead of it.
+/// `level_before` is the level's size when the order was sent; if the level has since grown by
+/// our size, the book already shows our order (its delta beat the POST response) and that
+/// part is not ahead of us.
+pub fn on_placed(s: &mut AppState, order_id: &str, level_before: f64) {
+    let Some(order) = s.my_open_orders.get(order_id) else {
+        return;
+    };
+    let level = level_size(s, order);
+    let ahead = if level >= level_before + order.size - 1e-9 {
+        (level - order.size).max(0.0)
+    } else {
+        level
+    };
+    s.queue_ahead.insert(order_id.to_string(), ahead);
+}
+
+/// Apply a depth update to `books` and move our orders up their levels. Size added to a level
+/// queues behind us; size removed (fills and cancels) is taken from the orders ahead of us in
+/// proportion to their share of the level's other size. Our estimate never exceeds what the
+/// level holds besides our order.
+pub fn apply_depth(s: &mut AppState, update: &DepthUpdate) {
+    let asset_id = update.asset_id().to_string();
+    let ours: Vec<(String, Order)> = s
+        .my_open_orders
+        .iter()
+        .filter(|(id, o)| o.asset_id == asset_id && s.queue_ahead.contains_key(*id))
+        .map(|(id, o)| (id.clone(), o.clone()))
+        .collect();
+    let before: Vec<f64> = ours.iter().map(|(_, o)| level_size(s, o)).collect();
+    s.books.entry(asset_id).or_default().apply(update);
+
+    for ((id, order), before) in ours.into_iter().zip(before) {
+        let after = level_size(s, &order);
+        let others_before = (before - order.size).max(0.0);
+        let others_after = (after - order.size).max(0.0);
+        let Some(ahead) = s.queue_ahead.get_mut(&id) else {
+            continue;
+        };
+        let removed = others_before - others_after;
+        if removed > 0.0 && others_before > 0.0 {
+            *ahead -= removed * *ahead / others_before;
+        }
+        *ahead = ahead.clamp(0.0, others_after);
+    }
+    let live = &s.my_open_orders;
+    s.queue_ahead.retain(|id, _| live.contains_key(id));
+}
+
+/// Size resting at `order`'s price level on its side of the book
+pub fn level_size(s: &AppState, order: &Order) -> f64 {
+    s.books
+        .get(&order.asset_id)
+        .map_or(0.0, |book| book.size_at(&order.side, order.price))
+}
+
+/// Share of the level's other size estimated ahead of our order; None if we have no estimate
+/// or the level holds nothing besides our order, where the queue is worth nothing
+pub fn ahead_fraction(s: &AppState, order_id: &str, order: &Order) -> Option<f64> {
+    let ahead = *s.queue_ahead.get(order_id)?;
+    let others = (level_size(s, order) - order.size).max(0.0);
+    (others > 0.0).then(|| ahead / others)
+}
+
+/// Whether `resting` is worth keeping instead of being replaced by `replacement`: its price is
+/// at or behind the replacement's (a bid no higher, an ask no lower) and within
+/// `keep_within_ticks` of it, it is no larger, and at most `max_ahead_fraction` of its level is
+/// ahead of it. A resting order more aggressive than the strategy now wants is never kept.
+pub fn worth_keeping(s: &AppState, order_id: &str, resting: &Order, replacement: &Order) -> bool {
+    let config = &s.market_config(&resting.asset_id).queue;
+    if config.keep_within_ticks == 0 || resting.size > replacement.size + 1e-9 {
+        return false;
+    }
+    let behind = match resting.side {
+        Side::Buy => resting.price <= replacement.price + 1e-9,
+        Side::Sell => resting.price >= replacement.price - 1e-9,
+    };
+    if !behind {
+        return false;
+    }
+    let ticks = ((replacement.price - resting.price) / s.tick_size(&resting.asset_id))
+        .abs()
+        .round() as u32;
+    ticks <= config.keep_within_ticks
+        && ahead_fraction(s, order_id, resting)
+            .is_some_and(|fraction| fraction <= config.max_ahead_fraction)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::test_support::{order, rest};
+
+    const TOKEN: &str = "yes";
+    const PRICE: f64 = 0.50;
+    const OURS: f64 = 10.0;
+
+    fn level(size: f64) -> DepthUpdate {
+        DepthUpdate::Level {
+            asset_id: TOKEN.to_string(),
+            side: Side::Buy,
+            price: PRICE,
+            size,
+        }
+    }
+
+    /// Our bid resting at a level of `others` plus our size, with `ahead` of it in front
+    fn resting(others: f64, ahead: f64) -> AppState {
+        let mut s = AppState::default();
+        rest(&mut s, "o1", order(TOKEN, Side::Buy, PRICE, OURS));
+        s.books
+            .entry(TOKEN.to_string())
+            .or_default()
+            .apply(&level(others + OURS));
+        s.queue_ahead.insert("o1".to_string(), ahead);
+        s
+    }
+
+    fn ahead(s: &AppState) -> f64 {
+        s.queue_ahead["o1"]
+    }
+
+    #[test]
+    fn shrinking_level_moves_us_up_in_proportion() {
+        let mut s = resting(100.0, 40.0);
+        apply_depth(&mut s, &level(50.0 + OURS));
+        assert!((ahead(&s) - 20.0).abs() < 1e-9);
+        apply_depth(&mut s, &level(10.0 + OURS));
+        assert!((ahead(&s) - 4.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn growing_level_queues_behind_us() {
+        let mut s = resting(100.0, 40.0);
+        apply_depth(&mut s, &level(150.0 + OURS));
+        assert!((ahead(&s) - 40.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn estimate_is_clamped_to_the_rest_of_the_level() {
+        let mut s = resting(100.0, 150.0);
+        apply_depth(&mut s, &level(50.0 + OURS));
+        assert!((ahead(&s) - 50.0).abs() < 1e-9);
+        apply_depth(&mut s, &level(0.0));
+        assert_eq!(ahead(&s), 0.0);
+    }
+
+    #[test]
+    fn estimates_of_gone_orders_are_dropped() {
+        let mut s = resting(100.0, 40.0);
+        s.my_open_orders.clear();
+        apply_depth(&mut s, &level(50.0));
+        assert!(s.queue_ahead.is_empty());
+    }
+
+    #[test]
+    fn only_orders_at_or_behind_the_replacement_are_kept() {
+        let mut s = resting(100.0, 20.0);
+        let bid = s.my_open_orders["o1"].clone();
+        let keeps = |s: &AppState, id: &str, resting: &Order, price: f64| {
+            let replacement = order(TOKEN, resting.side.clone(), price, OURS);
+            worth_keeping(s, id, resting, &replacement)
+        };
+        assert!(keeps(&s, "o1", &bid, PRICE));
+        assert!(keeps(&s, "o1", &bid, PRICE + 0.01));
+        assert!(!keeps(&s, "o1", &bid, PRICE - 0.01));
+        assert!(!keeps(&s, "o1", &bid, PRICE + 0.02));
+
+        let ask_price = PRICE + 0.02;
+        rest(&mut s, "o2", order(TOKEN, Side::Sell, ask_price, OURS));
+        s.books.get_mut(TOKEN).unwrap().apply(&DepthUpdate::Level {
+            asset_id: TOKEN.to_string(),
+            side: Side::Sell,
+            price: ask_price,
+            size: 100.0 + OURS,
+        });
+        s.queue_ahead.insert("o2".to_string(), 20.0);
+        let ask = s.my_open_orders["o2"].clone();
+        assert!(keeps(&s, "o2", &ask, ask_price - 0.01));
+        assert!(!keeps(&s, "o2", &ask, ask_price + 0.01));
+    }
+}
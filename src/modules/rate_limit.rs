@@ -0,0 +1,177 @@
+use crate::modules::logger;
+use std::collections::HashMap;
+use tokio::sync::Mutex;
+use tokio::time::{sleep, Duration, Instant};
+
+// -------------------- Published CLOB Limits --------------------
+// Polymarket enforces a short burst window and a longer sustained window per endpoint.
+// Each tuple is (requests, window in seconds). We run at SAFETY_FACTOR of the published
+// numbers so retries and clock skew never push us over the real limit.
+const POST_ORDER_LIMITS: [(f64, u64); 2] = [(3500.0, 10), (36000.0, 600)];
+const CANCEL_ORDER_LIMITS: [(f64, u64); 2] = [(3000.0, 10), (30000.0, 600)];
+const CANCEL_ALL_LIMITS: [(f64, u64); 2] = [(250.0, 10), (6000.0, 600)];
+const GET_ORDER_LIMITS: [(f64, u64); 1] = [(900.0, 10)];
+const SAFETY_FACTOR: f64 = 0.8;
+
+/// CLOB endpoints called from the execution layer, each with its own budget
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Endpoint {
+    PostOrder,
+    CancelOrder,
+    CancelAll,
+    GetOrder,
+}
+
+impl Endpoint {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Endpoint::PostOrder => "POST /order",
+            Endpoint::CancelOrder => "DELETE /order",
+            Endpoint::CancelAll => "DELETE /cancel-all",
+            Endpoint::GetOrder => "GET /order",
+        }
+    }
+}
+
+// -------------------- Token Bucket --------------------
+struct TokenBucket {
+    capacity: f64,
+    tokens: f64,
+    refill_per_sec: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    fn new(requests: f64, window_secs: u64) -> Self {
+        let capacity = (requests * SAFETY_FACTOR).floor().max(1.0);
+        Self {
+            capacity,
+            tokens: capacity,
+            refill_per_sec: capacity / window_secs as f64,
+            last_refill: Instant::now(),
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.duration_since(self.last_refill).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.refill_per_sec).min(self.capacity);
+        self.last_refill = now;
+    }
+
+    /// Time until one token is available (zero if one is available now)
+    fn wait_time(&self) -> Duration {
+        if self.tokens >= 1.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64((1.0 - self.tokens) / self.refill_per_sec)
+        }
+    }
+}
+
+// -------------------- Rate Limiter --------------------
+/// Per-endpoint limiter; a request must fit every window of its endpoint before it is sent
+pub struct RateLimiter {
+    buckets: Mutex<HashMap<Endpoint, Vec<TokenBucket>>>,
+}
+
+impl RateLimiter {
+    pub fn polymarket_defaults() -> Self {
+        let mut buckets = HashMap::new();
+        let mut add = |endpoint: Endpoint, limits: &[(f64, u64)]| {
+            buckets.insert(
+                endpoint,
+                limits
+                    .iter()
+                    .map(|(requests, window)| TokenBucket::new(*requests, *window))
+                    .collect(),
+            );
+        };
+        add(Endpoint::PostOrder, &POST_ORDER_LIMITS);
+        add(Endpoint::CancelOrder, &CANCEL_ORDER_LIMITS);
+        add(Endpoint::CancelAll, &CANCEL_ALL_LIMITS);
+        add(Endpoint::GetOrder, &GET_ORDER_LIMITS);
+        Self {
+            buckets: Mutex::new(buckets),
+        }
+    }
+
+    /// Wait until the endpoint has budget, then consume one token from each of its windows
+    pub async fn acquire(&self, endpoint: Endpoint) {
+        loop {
+            let wait = {
+                let mut buckets = self.buckets.lock().await;
+                let Some(windows) = buckets.get_mut(&endpoint) else {
+                    return;
+                };
+                let now = Instant::now();
+                let mut wait = Duration::ZERO;
+                for bucket in windows.iter_mut() {
+                    bucket.refill(now);
+                    wait = wait.max(bucket.wait_time());
+                }
+                if wait.is_zero() {
+                    for bucket in windows.iter_mut() {
+                        bucket.tokens -= 1.0;
+                    }
+                    return;
+                }
+                wait
+            };
+            logger::logln(format!(
+                "RateLimit: {} throttled locally for {}ms",
+                endpoint.as_str(),
+                wait.as_millis()
+            ));
+            sleep(wait).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bucket_is_sized_below_the_published_limit() {
+        let bucket = TokenBucket::new(100.0, 10);
+        assert_eq!(bucket.capacity, 80.0);
+        assert_eq!(bucket.tokens, 80.0);
+        assert_eq!(bucket.refill_per_sec, 8.0);
+        assert_eq!(TokenBucket::new(1.0, 10).capacity, 1.0);
+    }
+
+    #[test]
+    fn exhausted_bucket_waits_for_one_token() {
+        let mut bucket = TokenBucket::new(10.0, 2); // 8 tokens, 4 per second
+        assert_eq!(bucket.wait_time(), Duration::ZERO);
+        bucket.tokens = 0.0;
+        assert_eq!(bucket.wait_time(), Duration::from_millis(250));
+        bucket.tokens = 0.5;
+        assert_eq!(bucket.wait_time(), Duration::from_millis(125));
+    }
+
+    #[test]
+    fn bucket_refills_with_time_up_to_capacity() {
+        let mut bucket = TokenBucket::new(10.0, 2);
+        let start = bucket.last_refill;
+        bucket.tokens = 0.0;
+        bucket.refill(start + Duration::from_millis(500));
+        assert!((bucket.tokens - 2.0).abs() < 1e-9);
+        assert_eq!(bucket.wait_time(), Duration::ZERO);
+        bucket.refill(start + Duration::from_secs(60));
+        assert_eq!(bucket.tokens, bucket.capacity);
+    }
+
+    #[tokio::test]
+    async fn acquire_takes_a_token_from_every_window() {
+        let limiter = RateLimiter::polymarket_defaults();
+        limiter.acquire(Endpoint::CancelAll).await;
+        let buckets = limiter.buckets.lock().await;
+        for bucket in &buckets[&Endpoint::CancelAll] {
+            assert!(bucket.capacity - bucket.tokens > 0.99);
+            assert!(bucket.capacity - bucket.tokens < 1.0 + 1e-6);
+        }
+        let untouched = &buckets[&Endpoint::PostOrder][0];
+        assert_eq!(untouched.tokens, untouched.capacity);
+    }
+}
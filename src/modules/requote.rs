@@ -0,0 +1,195 @@
+use crate::modules::heartbeat;
+use crate::modules::queue;
+use crate::modules::types::{AppState, Order, OrderId};
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+// -------------------- Requote Defaults --------------------
+const DEFAULT_MIN_LIFETIME_SECS: f64 = 2.0;
+const DEFAULT_MAX_AGE_SECS: u64 = 120;
+const DEFAULT_MIN_MOVE_TICKS: u32 = 1; // 1 replaces on any price change
+
+/// `requote` in the config: when a strategy's new quotes replace the resting ones
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RequoteConfig {
+    pub min_lifetime_secs: f64, // an asset's quotes are not replaced sooner than this
+    pub max_age_secs: u64,      // an asset's quotes are refreshed at least this often; 0 = never
+    pub min_move_ticks: u32,    // smaller price changes keep the resting quote
+}
+
+impl Default for RequoteConfig {
+    fn default() -> Self {
+        Self {
+            min_lifetime_secs: DEFAULT_MIN_LIFETIME_SECS,
+            max_age_secs: DEFAULT_MAX_AGE_SECS,
+            min_move_ticks: DEFAULT_MIN_MOVE_TICKS,
+        }
+    }
+}
+
+/// Seconds since `asset_id`'s quotes were last placed; None if we never quoted it
+fn age(s: &AppState, asset_id: &str) -> Option<Duration> {
+    s.quoted_at.get(asset_id).map(Instant::elapsed)
+}
+
+/// Whether `asset_id`'s quotes are past `max_age_secs` and must be replaced even if unchanged
+pub fn expired(s: &AppState, asset_id: &str) -> bool {
+    let max_age = s.market_config(asset_id).requote.max_age_secs;
+    max_age > 0 && age(s, asset_id).is_some_and(|age| age.as_secs() >= max_age)
+}
+
+/// Markets (YES tokens) with a resting quote past its max age; the trading task forgets
+/// their mid bucket so the strategy re-prices them on the next update
+pub fn expired_markets(s: &AppState) -> Vec<String> {
+    let mut markets: Vec<String> = s
+        .my_open_orders
+        .values()
+        .filter(|o| expired(s, &o.asset_id))
+        .filter_map(|o| s.yes_token_of(&o.asset_id).map(str::to_string))
+        .collect();
+    markets.sort();
+    markets.dedup();
+    markets
+}
+
+/// Keep resting orders the strategy wants to replace when the replacement is too soon or too
+/// small a move. A resting order and an unmatched desired quote on the same token and side
+/// form a replacement; it is held back while the token's quotes are younger than
+/// `min_lifetime_secs`, or while the price moves fewer than `min_move_ticks` at the same size.
+/// A well-placed order in the queue (`[queue]`) is kept too, and counts as refreshed on an
+/// expired token. Pulls (no replacement) and new levels (nothing to replace) always pass, as
+/// does anything else on an expired token. Returns true if a replacement was held back only by
+/// the lifetime, so the caller can re-evaluate once it has passed.
+pub fn hold(
+    s: &mut AppState,
+    cancels: &mut Vec<(OrderId, Order)>,
+    desired: &mut Vec<Order>,
+) -> bool {
+    let mut held_by_lifetime = false;
+    let mut refreshed = Vec::new();
+    let state: &AppState = s;
+    cancels.retain(|(id, resting)| {
+        let s = state;
+        let replacement = desired
+            .iter()
+            .enumerate()
+            .filter(|(_, d)| d.asset_id == resting.asset_id && d.side == resting.side)
+            .min_by(|(_, a), (_, b)| {
+                (a.price - resting.price)
+                    .abs()
+                    .total_cmp(&(b.price - resting.price).abs())
+            })
+            .map(|(pos, d)| (pos, d.clone()));
+        let Some((pos, replacement)) = replacement else {
+            return true;
+        };
+        // About to lapse: the order must go whatever its queue place or age
+        if heartbeat::renewal_due(s, id) {
+            return true;
+        }
+        if queue::worth_keeping(s, id, resting, &replacement) {
+            if expired(s, &resting.asset_id) {
+                refreshed.push(resting.asset_id.clone());
+            }
+            desired.remove(pos);
+            return false;
+        }
+        if expired(s, &resting.asset_id) {
+            return true;
+        }
+        let config = &s.market_config(&resting.asset_id).requote;
+        let young = age(s, &resting.asset_id)
+            .is_some_and(|age| age.as_secs_f64() < config.min_lifetime_secs);
+        let ticks = ((replacement.price - resting.price) / s.tick_size(&resting.asset_id))
+            .abs()
+            .round() as u32;
+        let small_move =
+            ticks < config.min_move_ticks && (replacement.size - resting.size).abs() < 1e-9;
+        if !young && !small_move {
+            return true;
+        }
+        held_by_lifetime |= young && !small_move;
+        desired.remove(pos);
+        false
+    });
+    for asset_id in refreshed {
+        mark_quoted(s, &asset_id);
+    }
+    held_by_lifetime
+}
+
+/// Quotes for `asset_id` were just sent
+pub fn mark_quoted(s: &mut AppState, asset_id: &str) {
+    s.quoted_at.insert(asset_id.to_string(), Instant::now());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::test_support::{order, secs_ago};
+    use crate::modules::types::Side;
+
+    const TOKEN: &str = "yes";
+
+    fn bid(price: f64) -> Order {
+        order(TOKEN, Side::Buy, price, 10.0)
+    }
+
+    /// State whose `TOKEN` quotes were placed `secs` ago
+    fn quoted_secs_ago(secs: u64) -> AppState {
+        let mut s = AppState::default();
+        s.quoted_at.insert(TOKEN.to_string(), secs_ago(secs));
+        s
+    }
+
+    /// Hold a resting bid at `resting` against a wanted one at `wanted`: (held by the lifetime,
+    /// cancels left, creates left)
+    fn run(s: &mut AppState, resting: f64, wanted: f64) -> (bool, usize, usize) {
+        let mut cancels = vec![("o1".to_string(), bid(resting))];
+        let mut desired = vec![bid(wanted)];
+        let held = hold(s, &mut cancels, &mut desired);
+        (held, cancels.len(), desired.len())
+    }
+
+    #[test]
+    fn young_replacement_is_held_by_lifetime() {
+        let mut s = quoted_secs_ago(0);
+        assert_eq!(run(&mut s, 0.50, 0.53), (true, 0, 0));
+    }
+
+    #[test]
+    fn replacement_passes_once_lifetime_is_over() {
+        let mut s = quoted_secs_ago(5);
+        assert_eq!(run(&mut s, 0.50, 0.53), (false, 1, 1));
+    }
+
+    #[test]
+    fn small_move_is_held_without_asking_for_reevaluation() {
+        let mut s = quoted_secs_ago(5);
+        s.config.default.requote.min_move_ticks = 3;
+        assert_eq!(run(&mut s, 0.50, 0.52), (false, 0, 0));
+        assert_eq!(run(&mut s, 0.50, 0.53), (false, 1, 1));
+    }
+
+    #[test]
+    fn expired_quotes_are_always_replaced() {
+        let mut s = quoted_secs_ago(DEFAULT_MAX_AGE_SECS + 1);
+        s.config.default.requote.min_move_ticks = 3;
+        assert_eq!(run(&mut s, 0.50, 0.51), (false, 1, 1));
+    }
+
+    #[test]
+    fn pulls_and_new_levels_pass() {
+        let mut s = quoted_secs_ago(0);
+        let mut cancels = vec![("o1".to_string(), bid(0.50))];
+        let mut desired = Vec::new();
+        assert!(!hold(&mut s, &mut cancels, &mut desired));
+        assert_eq!(cancels.len(), 1);
+
+        let mut cancels = Vec::new();
+        let mut desired = vec![bid(0.50)];
+        assert!(!hold(&mut s, &mut cancels, &mut desired));
+        assert_eq!(desired.len(), 1);
+    }
+}
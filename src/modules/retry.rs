@@ -0,0 +1,311 @@
+use crate::modules::logger;
+use crate::modules::rate_limit::{Endpoint, RateLimiter};
+use serde_json::Value;
+use std::future::Future;
+use tokio::time::{sleep, Duration};
+
+// -------------------- Retry Policy --------------------
+const MAX_ATTEMPTS: u32 = 4; // First try plus three retries
+const BASE_DELAY_MS: u64 = 250;
+const MAX_DELAY_MS: u64 = 8_000;
+const RATE_LIMITED_DELAY_MS: u64 = 2_000; // Floor for backoff after a 429
+
+// Order rejections that will never succeed if sent again unchanged
+const FATAL_ORDER_ERRORS: [&str; 8] = [
+    "INVALID_ORDER_MIN_TICK_SIZE",
+    "INVALID_ORDER_MIN_SIZE",
+    "INVALID_ORDER_DUPLICATED",
+    "INVALID_ORDER_NOT_ENOUGH_BALANCE",
+    "INVALID_ORDER_EXPIRATION",
+    "FOK_ORDER_NOT_FILLED_ERROR",
+    "MARKET_NOT_READY",
+    "not enough balance",
+];
+
+// Substrings that mark an error as transient when no HTTP status is attached
+const TRANSIENT_MARKERS: [&str; 3] = ["timed out", "timeout", "connection"];
+
+/// How a failed CLOB call should be handled
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ErrorClass {
+    /// 429: back off harder before trying again
+    RateLimited,
+    /// 5xx, timeouts, dropped connections, transient exchange errors
+    Retriable,
+    /// Bad arguments or business-rule rejections; retrying cannot help
+    Fatal,
+    /// 5xx or timeout after the request went out: a non-idempotent call may have taken
+    /// effect, so it is not retried blindly
+    Unconfirmed,
+}
+
+/// Classify a client error by HTTP status when available, otherwise by message
+pub fn classify_error(err: &anyhow::Error) -> ErrorClass {
+    if let Some(req_err) = err.chain().find_map(|e| e.downcast_ref::<reqwest::Error>()) {
+        if let Some(status) = req_err.status() {
+            return classify_status(status.as_u16());
+        }
+        if req_err.is_timeout() || req_err.is_connect() || req_err.is_request() {
+            return ErrorClass::Retriable;
+        }
+    }
+    classify_message(&format!("{:#}", err))
+}
+
+/// Classify a failed non-idempotent call (`post_order`). Only a 429 or a failure before the
+/// request was sent is safe to repeat; a 5xx or timeout may hide an accepted request.
+pub fn classify_send_error(err: &anyhow::Error) -> ErrorClass {
+    if let Some(req_err) = err.chain().find_map(|e| e.downcast_ref::<reqwest::Error>()) {
+        if req_err.is_connect() {
+            return ErrorClass::Retriable;
+        }
+        if req_err.is_builder() {
+            return ErrorClass::Fatal;
+        }
+    }
+    match classify_error(err) {
+        ErrorClass::Retriable => ErrorClass::Unconfirmed,
+        class => class,
+    }
+}
+
+/// Inspect a `post_order` response body; returns `None` when the order was accepted
+pub fn classify_post_response(posted: &Value) -> Option<(ErrorClass, String)> {
+    let success = posted
+        .get("success")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(true);
+    let error_msg = posted
+        .get("errorMsg")
+        .or_else(|| posted.get("error"))
+        .and_then(|v| v.as_str())
+        .unwrap_or_default();
+    if success && error_msg.is_empty() {
+        return None;
+    }
+    let reason = if error_msg.is_empty() {
+        posted.to_string()
+    } else {
+        error_msg.to_string()
+    };
+    Some((classify_message(&reason), reason))
+}
+
+fn classify_status(status: u16) -> ErrorClass {
+    match status {
+        429 => ErrorClass::RateLimited,
+        500..=599 => ErrorClass::Retriable,
+        _ => ErrorClass::Fatal,
+    }
+}
+
+fn classify_message(msg: &str) -> ErrorClass {
+    let lower = msg.to_ascii_lowercase();
+    if let Some(status) = status_in_message(&lower) {
+        return classify_status(status);
+    }
+    if lower.contains("too many requests") {
+        return ErrorClass::RateLimited;
+    }
+    if FATAL_ORDER_ERRORS
+        .iter()
+        .any(|fatal| lower.contains(&fatal.to_ascii_lowercase()))
+    {
+        return ErrorClass::Fatal;
+    }
+    if TRANSIENT_MARKERS.iter().any(|t| lower.contains(t)) {
+        return ErrorClass::Retriable;
+    }
+    // Unknown failures are treated as fatal so a bad order is never hammered
+    ErrorClass::Fatal
+}
+
+/// An HTTP status spelled out in an error message ("status: 503", "status code 429")
+fn status_in_message(lower: &str) -> Option<u16> {
+    lower.match_indices("status").find_map(|(at, word)| {
+        let rest = lower[at + word.len()..].trim_start_matches([' ', ':', '=']);
+        let rest = rest
+            .strip_prefix("code")
+            .unwrap_or(rest)
+            .trim_start_matches([' ', ':', '=']);
+        let digits: String = rest.chars().take_while(char::is_ascii_digit).collect();
+        (digits.len() == 3)
+            .then(|| digits.parse().ok())
+            .flatten()
+            .filter(|status| (100..600).contains(status))
+    })
+}
+
+/// Exponential backoff with jitter; 429s never wait less than RATE_LIMITED_DELAY_MS
+pub fn backoff_delay(attempt: u32, class: ErrorClass) -> Duration {
+    let exp = BASE_DELAY_MS.saturating_mul(1u64 << attempt.min(10));
+    let floor = match class {
+        ErrorClass::RateLimited => RATE_LIMITED_DELAY_MS,
+        _ => 0,
+    };
+    let base = exp.max(floor).min(MAX_DELAY_MS);
+    Duration::from_millis(base + jitter_ms(base / 4))
+}
+
+fn jitter_ms(max: u64) -> u64 {
+    use std::time::{SystemTime, UNIX_EPOCH};
+    if max == 0 {
+        return 0;
+    }
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.subsec_nanos() as u64)
+        .unwrap_or(0);
+    nanos % max
+}
+
+/// Run `op` under the endpoint's rate limit, retrying rate-limited and retriable failures
+/// with backoff; fatal and unconfirmed failures are returned at once.
+/// `op` returns the error together with its class so callers can classify response bodies too.
+pub async fn with_retry<T, F, Fut>(
+    limiter: &RateLimiter,
+    endpoint: Endpoint,
+    label: &str,
+    mut op: F,
+) -> Result<T, (ErrorClass, String)>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, (ErrorClass, String)>>,
+{
+    let mut attempt = 0;
+    loop {
+        limiter.acquire(endpoint).await;
+        match op().await {
+            Ok(v) => return Ok(v),
+            Err((class, reason)) => {
+                attempt += 1;
+                let final_class = matches!(class, ErrorClass::Fatal | ErrorClass::Unconfirmed);
+                if final_class || attempt >= MAX_ATTEMPTS {
+                    return Err((class, reason));
+                }
+                let delay = backoff_delay(attempt, class);
+                logger::logln(format!(
+                    "Retry: {} failed ({:?}: {}). Attempt {}/{} in {}ms",
+                    label,
+                    class,
+                    reason,
+                    attempt + 1,
+                    MAX_ATTEMPTS,
+                    delay.as_millis()
+                ));
+                sleep(delay).await;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::cell::Cell;
+
+    #[test]
+    fn status_is_read_from_either_spelling() {
+        assert_eq!(status_in_message("request failed, status: 503"), Some(503));
+        assert_eq!(
+            status_in_message("http status code 429 returned"),
+            Some(429)
+        );
+        assert_eq!(status_in_message("status=400 bad request"), Some(400));
+        assert_eq!(status_in_message("status: 5030"), None);
+        assert_eq!(status_in_message("status: 42"), None);
+        assert_eq!(status_in_message("status: 999"), None);
+        assert_eq!(status_in_message("no status here"), None);
+    }
+
+    #[test]
+    fn messages_are_classified_by_status_then_text() {
+        let class = |msg: &str| classify_error(&anyhow::anyhow!(msg.to_string()));
+        assert_eq!(class("Status: 429"), ErrorClass::RateLimited);
+        assert_eq!(class("status code 502"), ErrorClass::Retriable);
+        assert_eq!(class("status: 400 timed out"), ErrorClass::Fatal);
+        assert_eq!(class("Too Many Requests"), ErrorClass::RateLimited);
+        assert_eq!(class("INVALID_ORDER_MIN_SIZE"), ErrorClass::Fatal);
+        assert_eq!(class("not enough balance / allowance"), ErrorClass::Fatal);
+        assert_eq!(class("operation timed out"), ErrorClass::Retriable);
+        assert_eq!(class("gateway Timeout"), ErrorClass::Retriable);
+        assert_eq!(class("connection reset by peer"), ErrorClass::Retriable);
+        assert_eq!(class("something odd happened"), ErrorClass::Fatal);
+    }
+
+    #[test]
+    fn a_send_that_may_have_landed_is_unconfirmed() {
+        let class = |msg: &str| classify_send_error(&anyhow::anyhow!(msg.to_string()));
+        assert_eq!(class("status: 503"), ErrorClass::Unconfirmed);
+        assert_eq!(class("request timed out"), ErrorClass::Unconfirmed);
+        assert_eq!(class("status: 429"), ErrorClass::RateLimited);
+        assert_eq!(class("INVALID_ORDER_DUPLICATED"), ErrorClass::Fatal);
+    }
+
+    #[test]
+    fn post_responses_are_classified_by_their_error() {
+        assert_eq!(
+            classify_post_response(&json!({"success": true, "orderID": "o1"})),
+            None
+        );
+        assert_eq!(classify_post_response(&json!({"orderID": "o1"})), None);
+        assert_eq!(
+            classify_post_response(
+                &json!({"success": false, "errorMsg": "INVALID_ORDER_MIN_TICK_SIZE"})
+            ),
+            Some((ErrorClass::Fatal, "INVALID_ORDER_MIN_TICK_SIZE".to_string()))
+        );
+        assert_eq!(
+            classify_post_response(&json!({"error": "Too many requests"})),
+            Some((ErrorClass::RateLimited, "Too many requests".to_string()))
+        );
+        // A bare failure keeps the whole body as the reason
+        let (class, reason) = classify_post_response(&json!({"success": false})).unwrap();
+        assert_eq!(class, ErrorClass::Fatal);
+        assert!(reason.contains("success"));
+    }
+
+    #[test]
+    fn rate_limited_backoff_never_undercuts_its_floor() {
+        let floor = Duration::from_millis(RATE_LIMITED_DELAY_MS);
+        assert!(backoff_delay(1, ErrorClass::RateLimited) >= floor);
+        assert!(backoff_delay(1, ErrorClass::Retriable) < floor);
+        let cap = Duration::from_millis(MAX_DELAY_MS + MAX_DELAY_MS / 4);
+        assert!(backoff_delay(30, ErrorClass::Retriable) < cap);
+    }
+
+    #[tokio::test]
+    async fn fatal_and_unconfirmed_failures_are_not_retried() {
+        let limiter = RateLimiter::polymarket_defaults();
+        for class in [ErrorClass::Fatal, ErrorClass::Unconfirmed] {
+            let calls = Cell::new(0);
+            let result: Result<(), _> = with_retry(&limiter, Endpoint::PostOrder, "test", || {
+                calls.set(calls.get() + 1);
+                async move { Err((class, "rejected".to_string())) }
+            })
+            .await;
+            assert_eq!(result, Err((class, "rejected".to_string())));
+            assert_eq!(calls.get(), 1);
+        }
+    }
+
+    #[tokio::test]
+    async fn a_retriable_failure_is_tried_again() {
+        let limiter = RateLimiter::polymarket_defaults();
+        let calls = Cell::new(0);
+        let result = with_retry(&limiter, Endpoint::GetOrder, "test", || {
+            calls.set(calls.get() + 1);
+            let attempt = calls.get();
+            async move {
+                if attempt == 1 {
+                    Err((ErrorClass::Retriable, "timed out".to_string()))
+                } else {
+                    Ok(attempt)
+                }
+            }
+        })
+        .await;
+        assert_eq!(result, Ok(2));
+    }
+}
@@ -0,0 +1,178 @@
+use crate::modules::skew::dollar_imbalance;
+use crate::modules::strategy::YesBook;
+use crate::modules::types::{AppState, Side};
+use serde::{Deserialize, Serialize};
+
+// -------------------- Rewards Defaults --------------------
+const DEFAULT_BAND_FRACTION: f64 = 0.8; // stay inside 80% of max_spread so small mid moves keep us in
+const SINGLE_SIDED_DIVISOR: f64 = 3.0; // Polymarket's `c`: one-sided liquidity scores a third
+const TWO_SIDED_LOW: f64 = 0.10; // outside [0.10, 0.90] only two-sided liquidity scores
+const TWO_SIDED_HIGH: f64 = 0.90;
+
+/// `rewards` in the config
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RewardsConfig {
+    pub enabled: bool,
+    pub band_fraction: f64, // fraction of the market's max_spread our quotes are pulled into
+}
+
+impl Default for RewardsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            band_fraction: DEFAULT_BAND_FRACTION,
+        }
+    }
+}
+
+/// A market's liquidity reward terms from its metadata
+#[derive(Clone, Debug)]
+pub struct RewardParams {
+    pub min_size: f64,   // shares an order needs to score
+    pub max_spread: f64, // distance from the midpoint that still scores, in price
+    pub daily_rate: f64, // USDC per day shared by all qualifying makers
+}
+
+impl RewardParams {
+    /// Polymarket's order score: ((v - s) / v)^2 * size inside the band, 0 outside or under min size
+    fn order_score(&self, distance: f64, size: f64) -> f64 {
+        if size < self.min_size || distance >= self.max_spread {
+            return 0.0;
+        }
+        ((self.max_spread - distance) / self.max_spread).powi(2) * size
+    }
+}
+
+/// Where qualifying quotes must sit for the current book, and which sides may be tightened
+#[derive(Clone, Debug)]
+pub struct RewardBand {
+    pub bid_floor: f64, // lowest bid that still scores, on the tick grid
+    pub ask_cap: f64,   // highest ask that still scores, on the tick grid
+    pub min_size: f64,
+    tighten_bid: bool, // false while long YES beyond max_inventory_imbalance
+    tighten_ask: bool, // false while short YES beyond it
+}
+
+impl RewardBand {
+    /// Pull quotes that sit outside the band into it. Never widens a quote.
+    pub fn fit(&self, bid: f64, ask: f64) -> (f64, f64) {
+        let bid = if self.tighten_bid {
+            bid.max(self.bid_floor)
+        } else {
+            bid
+        };
+        let ask = if self.tighten_ask {
+            ask.min(self.ask_cap)
+        } else {
+            ask
+        };
+        (bid, ask)
+    }
+}
+
+/// The reward band for `book` when rewards mode is on and the market pays rewards
+pub fn band(book: &YesBook, s: &AppState) -> Option<RewardBand> {
+    let config = s.market_config(&book.yes_token);
+    if !config.rewards.enabled {
+        return None;
+    }
+    let params = s.reward_params.get(&book.yes_token)?;
+    let tick = s.tick_size(&book.yes_token);
+    let ticks_per_unit = (1.0 / tick).round();
+    let mid = book.mid();
+    let reach = params.max_spread * config.rewards.band_fraction;
+    // Inventory limits win over rewards: never tighten the side that grows an excess position
+    let imbalance = dollar_imbalance(book, s);
+    let limit = config.max_inventory_imbalance;
+    Some(RewardBand {
+        bid_floor: ((mid - reach) * ticks_per_unit).ceil() / ticks_per_unit,
+        ask_cap: ((mid + reach) * ticks_per_unit).floor() / ticks_per_unit,
+        min_size: params.min_size,
+        tighten_bid: imbalance < limit,
+        tighten_ask: imbalance > -limit,
+    })
+}
+
+/// Our slice of a market's reward pool, from the current book
+#[derive(Clone, Debug)]
+pub struct RewardEstimate {
+    pub our_score: f64,
+    pub total_score: f64,
+    pub share: f64,
+    pub daily: f64, // expected USDC per day at this share
+}
+
+/// Estimate our share of `yes_token`'s rewards: our resting orders scored against everything
+/// in the YES and NO depth books (which include them), both seen in YES prices, with the
+/// two-sided adjustment applied to both
+pub fn estimate(s: &AppState, yes_token: &str) -> Option<RewardEstimate> {
+    let params = s.reward_params.get(yes_token)?;
+    let no_token = s.token_pairs.get(yes_token)?;
+    let (best_bid, best_ask, _) = s.last_prices.get(yes_token)?;
+    let mid = (best_bid + best_ask) / 2.0;
+
+    // Ours, in YES terms: selling NO at p is a YES bid at 1 - p
+    let (mut our_bids, mut our_asks) = (0.0, 0.0);
+    for order in s.my_open_orders.values() {
+        let (side, price) = if order.asset_id == yes_token {
+            (order.side.clone(), order.price)
+        } else if &order.asset_id == no_token {
+            let side = match order.side {
+                Side::Buy => Side::Sell,
+                Side::Sell => Side::Buy,
+            };
+            (side, 1.0 - order.price)
+        } else {
+            continue;
+        };
+        let score = params.order_score((price - mid).abs(), order.size);
+        match side {
+            Side::Buy => our_bids += score,
+            Side::Sell => our_asks += score,
+        }
+    }
+
+    // The NO book in YES terms: a NO ask at p is a YES bid at 1 - p, a NO bid a YES ask
+    let (mut book_bids, mut book_asks) = (0.0, 0.0);
+    if let Some(depth) = s.books.get(yes_token) {
+        for (price, size) in depth.levels(&Side::Buy) {
+            book_bids += params.order_score(mid - price, size);
+        }
+        for (price, size) in depth.levels(&Side::Sell) {
+            book_asks += params.order_score(price - mid, size);
+        }
+    }
+    if let Some(depth) = s.books.get(no_token) {
+        for (price, size) in depth.levels(&Side::Sell) {
+            book_bids += params.order_score(mid - (1.0 - price), size);
+        }
+        for (price, size) in depth.levels(&Side::Buy) {
+            book_asks += params.order_score((1.0 - price) - mid, size);
+        }
+    }
+
+    let our_score = two_sided_score(our_bids, our_asks, mid);
+    let total_score = two_sided_score(book_bids.max(our_bids), book_asks.max(our_asks), mid);
+    let share = if total_score > 0.0 {
+        (our_score / total_score).min(1.0)
+    } else {
+        0.0
+    };
+    Some(RewardEstimate {
+        our_score,
+        total_score,
+        share,
+        daily: share * params.daily_rate,
+    })
+}
+
+/// Polymarket's Q_min: one-sided liquidity counts at 1/c inside [0.10, 0.90], not at all outside
+fn two_sided_score(bids: f64, asks: f64, mid: f64) -> f64 {
+    let both = bids.min(asks);
+    if (TWO_SIDED_LOW..=TWO_SIDED_HIGH).contains(&mid) {
+        both.max(bids.max(asks) / SINGLE_SIDED_DIVISOR)
+    } else {
+        both
+    }
+}
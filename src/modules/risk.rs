@@ -0,0 +1,203 @@
+use crate::modules::limits::{self, LimitScope};
+use crate::modules::types::{AppState, Order, Side};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+// -------------------- Risk Gate Defaults --------------------
+const DEFAULT_MIN_PRICE: f64 = 0.01;
+const DEFAULT_MAX_PRICE: f64 = 0.99;
+const DEFAULT_MAX_ORDER_NOTIONAL: f64 = 500.0;
+const DEFAULT_MAX_OPEN_NOTIONAL_PER_ASSET: f64 = 1000.0;
+const DEFAULT_MIN_ORDER_SIZE: f64 = 5.0; // Polymarket minimum order size in shares
+const PRICE_EPSILON: f64 = 1e-9;
+
+/// Pre-trade limits applied to every order before it is signed (`risk` in the config)
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RiskLimits {
+    pub min_price: f64,
+    pub max_price: f64,
+    pub max_order_notional: f64, // price * size of a single order
+    pub max_open_notional_per_asset: f64, // resting + new order notional per token
+    pub min_order_size: f64,     // shares
+}
+
+impl Default for RiskLimits {
+    fn default() -> Self {
+        Self {
+            min_price: DEFAULT_MIN_PRICE,
+            max_price: DEFAULT_MAX_PRICE,
+            max_order_notional: DEFAULT_MAX_ORDER_NOTIONAL,
+            max_open_notional_per_asset: DEFAULT_MAX_OPEN_NOTIONAL_PER_ASSET,
+            min_order_size: DEFAULT_MIN_ORDER_SIZE,
+        }
+    }
+}
+
+/// Why the gate refused an order
+#[derive(Clone, Debug)]
+pub enum RiskRejection {
+    PriceOutOfBand {
+        price: f64,
+        min: f64,
+        max: f64,
+    },
+    OffTick {
+        price: f64,
+        tick: f64,
+    },
+    OrderNotional {
+        notional: f64,
+        max: f64,
+    },
+    OpenNotional {
+        open: f64,
+        max: f64,
+    },
+    BelowMinSize {
+        size: f64,
+        min: f64,
+    },
+    Limit {
+        scope: LimitScope,
+        what: &'static str,
+        value: f64,
+        max: f64,
+    },
+    SelfCross {
+        resting_id: String,
+        resting_price: f64,
+    },
+}
+
+impl fmt::Display for RiskRejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RiskRejection::PriceOutOfBand { price, min, max } => {
+                write!(
+                    f,
+                    "price {:.4} outside band [{:.4}, {:.4}]",
+                    price, min, max
+                )
+            }
+            RiskRejection::OffTick { price, tick } => {
+                write!(f, "price {:.4} not aligned to tick {}", price, tick)
+            }
+            RiskRejection::OrderNotional { notional, max } => {
+                write!(f, "order notional ${:.2} exceeds ${:.2}", notional, max)
+            }
+            RiskRejection::OpenNotional { open, max } => write!(
+                f,
+                "open notional on asset would be ${:.2} (max ${:.2})",
+                open, max
+            ),
+            RiskRejection::BelowMinSize { size, min } => {
+                write!(f, "size {:.2} below minimum {:.2}", size, min)
+            }
+            RiskRejection::Limit {
+                scope,
+                what,
+                value,
+                max,
+            } => write!(
+                f,
+                "{} of {} would be {:.2} (max {:.2})",
+                what, scope, value, max
+            ),
+            RiskRejection::SelfCross {
+                resting_id,
+                resting_price,
+            } => write!(
+                f,
+                "would cross our resting order {} @ {:.4}",
+                resting_id, resting_price
+            ),
+        }
+    }
+}
+
+// -------------------- Gate --------------------
+/// Run every pre-trade check against the current state; first failure wins
+pub fn check_order(order: &Order, state: &AppState) -> Result<(), RiskRejection> {
+    let limits = &state.market_config(&order.asset_id).risk;
+
+    if order.price < limits.min_price - PRICE_EPSILON
+        || order.price > limits.max_price + PRICE_EPSILON
+    {
+        return Err(RiskRejection::PriceOutOfBand {
+            price: order.price,
+            min: limits.min_price,
+            max: limits.max_price,
+        });
+    }
+
+    let tick = state.tick_size(&order.asset_id);
+    if tick > 0.0 && ((order.price / tick).round() * tick - order.price).abs() > PRICE_EPSILON {
+        return Err(RiskRejection::OffTick {
+            price: order.price,
+            tick,
+        });
+    }
+
+    if order.size < limits.min_order_size {
+        return Err(RiskRejection::BelowMinSize {
+            size: order.size,
+            min: limits.min_order_size,
+        });
+    }
+
+    let notional = order.price * order.size;
+    if notional > limits.max_order_notional {
+        return Err(RiskRejection::OrderNotional {
+            notional,
+            max: limits.max_order_notional,
+        });
+    }
+
+    let open: f64 = state
+        .my_open_orders
+        .values()
+        .filter(|o| o.asset_id == order.asset_id)
+        .map(|o| o.price * o.size)
+        .sum::<f64>()
+        + notional;
+    if open > limits.max_open_notional_per_asset {
+        return Err(RiskRejection::OpenNotional {
+            open,
+            max: limits.max_open_notional_per_asset,
+        });
+    }
+
+    limits::check_order(order, notional, state)?;
+    check_self_cross(order, state)
+}
+
+/// Reject orders that would trade against our own resting orders, either directly on the
+/// same token or through the complementary token (YES bid + NO bid >= 1 matches by minting).
+fn check_self_cross(order: &Order, state: &AppState) -> Result<(), RiskRejection> {
+    let complement = state.token_pairs.get(&order.asset_id);
+    for (id, resting) in &state.my_open_orders {
+        let crosses = if resting.asset_id == order.asset_id {
+            match (&order.side, &resting.side) {
+                (Side::Buy, Side::Sell) => order.price >= resting.price - PRICE_EPSILON,
+                (Side::Sell, Side::Buy) => order.price <= resting.price + PRICE_EPSILON,
+                _ => false,
+            }
+        } else if Some(&resting.asset_id) == complement {
+            match (&order.side, &resting.side) {
+                (Side::Buy, Side::Buy) => order.price + resting.price >= 1.0 - PRICE_EPSILON,
+                (Side::Sell, Side::Sell) => order.price + resting.price <= 1.0 + PRICE_EPSILON,
+                _ => false,
+            }
+        } else {
+            false
+        };
+        if crosses {
+            return Err(RiskRejection::SelfCross {
+                resting_id: id.clone(),
+                resting_price: resting.price,
+            });
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,65 @@
+use crate::modules::strategy::YesBook;
+use crate::modules::types::AppState;
+use serde::{Deserialize, Serialize};
+
+// -------------------- Skew Defaults --------------------
+const DEFAULT_MAX_SHIFT: f64 = 0.01; // Full shift once the imbalance reaches max_inventory_imbalance
+const DEFAULT_STEEPNESS: f64 = 3.0; // Exponential curve only; higher = flatter near zero
+
+/// How the price shift grows with the dollar imbalance
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SkewCurve {
+    Off,
+    /// Shift proportional to the imbalance
+    Linear,
+    /// Little shift for small imbalances, ramping up sharply toward the limit
+    Exponential,
+}
+
+/// `skew` in the config
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SkewConfig {
+    pub curve: SkewCurve,
+    pub max_shift: f64, // YES price shift at (and beyond) max_inventory_imbalance
+    pub steepness: f64,
+}
+
+impl Default for SkewConfig {
+    fn default() -> Self {
+        Self {
+            curve: SkewCurve::Linear,
+            max_shift: DEFAULT_MAX_SHIFT,
+            steepness: DEFAULT_STEEPNESS,
+        }
+    }
+}
+
+impl SkewConfig {
+    /// Signed YES price shift for a signed dollar imbalance (positive = long YES).
+    /// Both quotes move by it: long YES lowers the ask (sell faster) and the bid (buy less),
+    /// short YES raises both.
+    pub fn shift(&self, imbalance_dollars: f64, max_imbalance: f64) -> f64 {
+        if max_imbalance <= 0.0 {
+            return 0.0;
+        }
+        let x = (imbalance_dollars / max_imbalance).clamp(-1.0, 1.0);
+        let magnitude = match self.curve {
+            SkewCurve::Off => 0.0,
+            SkewCurve::Linear => x.abs(),
+            SkewCurve::Exponential if self.steepness > 0.0 => {
+                ((self.steepness * x.abs()).exp() - 1.0) / (self.steepness.exp() - 1.0)
+            }
+            SkewCurve::Exponential => x.abs(),
+        };
+        -x.signum() * magnitude * self.max_shift
+    }
+}
+
+/// YES minus NO inventory in dollars at the current mid
+pub fn dollar_imbalance(book: &YesBook, state: &AppState) -> f64 {
+    let held = |token: &str| state.inventory.get(token).copied().unwrap_or(0.0);
+    let mid = book.mid();
+    held(&book.yes_token) * mid - held(&book.no_token) * (1.0 - mid)
+}
@@ -0,0 +1,203 @@
+use crate::modules::avellaneda::AvellanedaStoikov;
+use crate::modules::edge_strategy::EdgeMarketMaker;
+use crate::modules::types::{AppState, MarketUpdate, Order, OrderId, Side};
+
+// -------------------- Strategy Interface --------------------
+/// What a strategy wants on the book after handling an event
+#[derive(Clone, Debug)]
+pub enum StrategyAction {
+    /// Leave resting quotes untouched
+    Keep,
+    /// Make our resting orders match exactly this set (empty = pull all quotes).
+    /// The trading task diffs it against open orders and only cancels/creates the difference.
+    Quote(Vec<Order>),
+    /// Cancel the market's resting orders right away (risk event)
+    CancelAll,
+}
+
+/// One of our orders traded
+#[derive(Clone, Debug)]
+pub struct Fill {
+    pub order_id: OrderId,
+    pub asset_id: String,
+    pub side: Side,
+    pub price: f64,
+    pub size: f64,
+}
+
+/// Lifecycle event for one of our orders from the user channel (PLACEMENT/UPDATE/CANCELLATION)
+#[derive(Clone, Debug)]
+pub struct OrderEvent {
+    pub order_id: OrderId,
+    pub asset_id: String,
+    pub msg_type: String,
+}
+
+/// Events delivered to the trading task besides market data
+#[derive(Clone, Debug)]
+pub enum StrategyEvent {
+    Fill(Fill),
+    Order(OrderEvent),
+}
+
+/// A quoting strategy. Hooks run on the trading task with the state lock held, so they
+/// must not block; they read and update `AppState` directly and return the desired quotes.
+pub trait Strategy: Send {
+    fn name(&self) -> &'static str;
+
+    fn on_market_update(&mut self, update: &MarketUpdate, state: &mut AppState) -> StrategyAction;
+
+    fn on_fill(&mut self, _fill: &Fill, _state: &mut AppState) -> StrategyAction {
+        StrategyAction::Keep
+    }
+
+    fn on_order_event(&mut self, _event: &OrderEvent, _state: &mut AppState) -> StrategyAction {
+        StrategyAction::Keep
+    }
+
+    /// Called roughly once per second
+    fn on_timer(&mut self, _state: &mut AppState) -> StrategyAction {
+        StrategyAction::Keep
+    }
+}
+
+// -------------------- YES/NO Helpers --------------------
+/// A binary market's two outcome tokens, with the book of either token seen in YES prices
+#[derive(Clone, Debug)]
+pub struct YesBook {
+    pub yes_token: String,
+    pub no_token: String,
+    pub best_bid: f64,
+    pub best_ask: f64,
+}
+
+impl YesBook {
+    /// Map an update for a running market's YES or NO token onto its YES book (NO bid = 1 - YES ask)
+    pub fn from_update(update: &MarketUpdate, state: &AppState) -> Option<Self> {
+        let yes_token = state.yes_token_of(&update.asset_id)?.to_string();
+        let no_token = state.token_pairs.get(&yes_token)?.clone();
+        let (best_bid, best_ask) = if update.asset_id == yes_token {
+            (update.best_bid, update.best_ask)
+        } else if update.asset_id == no_token {
+            (1.0 - update.best_ask, 1.0 - update.best_bid)
+        } else {
+            return None;
+        };
+        Some(Self {
+            yes_token,
+            no_token,
+            best_bid,
+            best_ask,
+        })
+    }
+
+    pub fn mid(&self) -> f64 {
+        (self.best_bid + self.best_ask) / 2.0
+    }
+}
+
+/// Shares of each outcome token not yet committed to a sell in the quotes being built
+pub struct SellBudget {
+    yes: f64,
+    no: f64,
+}
+
+impl SellBudget {
+    pub fn new(book: &YesBook, state: &AppState) -> Self {
+        // Pairs being merged are about to leave the inventory
+        let reserved = state.merge_reserved(&book.yes_token);
+        let held = |token: &str| state.inventory.get(token).copied().unwrap_or(0.0) - reserved;
+        Self {
+            yes: held(&book.yes_token),
+            no: held(&book.no_token),
+        }
+    }
+
+    /// Commit what `order` needs: a sell takes its size of the token, a buy nothing.
+    /// False, committing nothing, when not enough is left.
+    pub fn reserve(&mut self, book: &YesBook, order: &Order) -> bool {
+        if order.side == Side::Buy {
+            return true;
+        }
+        let left = if order.asset_id == book.yes_token {
+            &mut self.yes
+        } else if order.asset_id == book.no_token {
+            &mut self.no
+        } else {
+            return false;
+        };
+        if *left < order.size {
+            return false;
+        }
+        *left -= order.size;
+        true
+    }
+}
+
+/// Express a quote in YES prices as an order on whichever token we can fill it with.
+/// A YES bid sells NO at 1 - price when enough NO is left in the budget, otherwise buys
+/// YES; a YES ask sells YES when enough YES is left, otherwise buys NO at 1 - price.
+pub fn yes_quote(
+    book: &YesBook,
+    side: Side,
+    price: f64,
+    size: f64,
+    budget: &mut SellBudget,
+    state: &AppState,
+) -> Order {
+    let (asset_id, order_side, order_price) = match side {
+        Side::Buy if budget.no >= size => {
+            budget.no -= size;
+            (
+                &book.no_token,
+                Side::Sell,
+                complement_price(price, state.tick_size(&book.no_token)),
+            )
+        }
+        Side::Buy => (&book.yes_token, Side::Buy, price),
+        Side::Sell if budget.yes >= size => {
+            budget.yes -= size;
+            (&book.yes_token, Side::Sell, price)
+        }
+        Side::Sell => (
+            &book.no_token,
+            Side::Buy,
+            complement_price(price, state.tick_size(&book.no_token)),
+        ),
+    };
+    Order {
+        id: None,
+        asset_id: asset_id.clone(),
+        side: order_side,
+        price: order_price,
+        size,
+    }
+}
+
+/// Which YES side an order quotes: buying YES or selling NO is our bid, the rest our ask
+pub fn yes_side(book: &YesBook, order: &Order) -> Side {
+    match (order.asset_id == book.yes_token, &order.side) {
+        (true, side) => side.clone(),
+        (false, Side::Buy) => Side::Sell,
+        (false, Side::Sell) => Side::Buy,
+    }
+}
+
+/// 1 - price, snapped back onto the tick grid to drop float noise
+pub fn complement_price(price: f64, tick: f64) -> f64 {
+    let ticks_per_unit = (1.0 / tick).round();
+    ((1.0 - price) * ticks_per_unit).round() / ticks_per_unit
+}
+
+// -------------------- Registry --------------------
+pub const STRATEGY_EDGE: &str = "edge";
+pub const STRATEGY_AVELLANEDA: &str = "avellaneda";
+
+/// Build a strategy by its config name
+pub fn build_strategy(name: &str) -> Option<Box<dyn Strategy>> {
+    match name {
+        STRATEGY_EDGE => Some(Box::new(EdgeMarketMaker::new())),
+        STRATEGY_AVELLANEDA => Some(Box::new(AvellanedaStoikov::new())),
+        _ => None,
+    }
+}
@@ -0,0 +1,48 @@
+//! Fixtures shared by the unit tests
+use crate::modules::market_manager::MarketSpec;
+use crate::modules::types::{AppState, Order, Side};
+use std::time::{Duration, Instant};
+
+/// An order not yet sent
+pub fn order(asset_id: &str, side: Side, price: f64, size: f64) -> Order {
+    Order {
+        id: None,
+        asset_id: asset_id.to_string(),
+        side,
+        price,
+        size,
+    }
+}
+
+/// Run the market `yes_token` (NO token `<yes_token>-no`) with its YES book at `bid`/`ask`;
+/// returns the NO token
+pub fn add_market(s: &mut AppState, yes_token: &str, bid: f64, ask: f64) -> String {
+    let no_token = format!("{}-no", yes_token);
+    s.markets.insert(
+        yes_token.to_string(),
+        MarketSpec {
+            condition_id: format!("{}-condition", yes_token),
+            yes_token: yes_token.to_string(),
+            no_token: no_token.clone(),
+        },
+    );
+    s.token_pairs
+        .insert(yes_token.to_string(), no_token.clone());
+    s.token_pairs
+        .insert(no_token.clone(), yes_token.to_string());
+    s.last_prices.insert(yes_token.to_string(), (bid, ask, 0));
+    no_token
+}
+
+/// `order` resting on the exchange as `id`
+pub fn rest(s: &mut AppState, id: &str, mut order: Order) {
+    order.id = Some(id.to_string());
+    s.my_open_orders.insert(id.to_string(), order);
+}
+
+/// The instant `secs` seconds ago
+pub fn secs_ago(secs: u64) -> Instant {
+    Instant::now()
+        .checked_sub(Duration::from_secs(secs))
+        .expect("test clock is past the offset")
+}
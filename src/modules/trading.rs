@@ -1,261 +1,366 @@
+use crate::modules::circuit_breaker;
+use crate::modules::edge_strategy::EdgeMarketMaker;
+use crate::modules::heartbeat::{self, Component};
+use crate::modules::limits::{self, BreachAction};
 use crate::modules::logger;
-use crate::modules::types::{AppState, BotCommand, MarketUpdate, Order, OrderId, Side};
+use crate::modules::markout;
+use crate::modules::pause;
+use crate::modules::requote;
+use crate::modules::strategy::{
+    build_strategy, Strategy, StrategyAction, StrategyEvent, STRATEGY_EDGE,
+};
+use crate::modules::types::{AppState, BotCommand, CommandSender, MarketUpdate, Order, OrderId};
+use crate::modules::unwind;
+use crate::modules::volatility;
+use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::{mpsc, Mutex};
+use tokio::time::Duration;
 
 // -------------------- Trading Logic Task --------------------
+/// Feeds market data, fills, order events and a 1s timer into one strategy instance per
+/// running market, named by that market's config, and turns the quotes each wants into
+/// Create/Cancel commands for its own tokens. Disabled markets get their quotes pulled, a
+/// config reload that names another strategy swaps it in place, and instances of markets
+/// the manager stopped are dropped. Market updates pass the circuit breaker first.
 pub async fn trading_logic_task(
     mut market_rx: mpsc::Receiver<MarketUpdate>,
-    cmd_tx: mpsc::Sender<BotCommand>,
+    mut event_rx: mpsc::Receiver<StrategyEvent>,
+    cmd_tx: CommandSender,
     state: Arc<Mutex<AppState>>,
 ) {
-    while let Some(update) = market_rx.recv().await {
+    // yes_token -> instance
+    let mut strategies: HashMap<String, Box<dyn Strategy>> = HashMap::new();
+    // Beat from our own timer so a quiet market does not look like a hung loop
+    let mut heartbeat_timer = tokio::time::interval(Duration::from_secs(1));
+    loop {
+        let commands = tokio::select! {
+            _ = heartbeat_timer.tick() => {
+                heartbeat::beat(&state, Component::Trading).await;
+                let mut s = state.lock().await;
+                pause::review(&mut s);
+                limits::review(&mut s);
+                unwind::review(&mut s);
+                markout::sample(&mut s);
+                strategies.retain(|yes_token, _| s.markets.contains_key(yes_token));
+                for yes_token in requote::expired_markets(&s) {
+                    s.last_mid_bucket.remove(&yes_token);
+                }
+                let mut markets: Vec<String> = s.markets.keys().cloned().collect();
+                markets.sort();
+                let mut commands = Vec::new();
+                for yes_token in markets {
+                    let action = if s.market_config(&yes_token).enabled {
+                        let action = strategy_for(&mut strategies, &mut s, &yes_token).on_timer(&mut s);
+                        renew_due(action, &yes_token, &s)
+                    } else {
+                        pull_quotes(&mut s, &yes_token)
+                    };
+                    commands.extend(plan_commands(action, &yes_token, &mut s));
+                }
+                commands
+            }
+            event = event_rx.recv() => {
+                let Some(event) = event else {
+                    break;
+                };
+                let mut s = state.lock().await;
+                let asset_id = match &event {
+                    StrategyEvent::Fill(fill) => &fill.asset_id,
+                    StrategyEvent::Order(order_event) => &order_event.asset_id,
+                };
+                let Some(yes_token) = s.yes_token_of(asset_id).map(str::to_string) else {
+                    continue;
+                };
+                let strategy = strategy_for(&mut strategies, &mut s, &yes_token);
+                let action = match &event {
+                    StrategyEvent::Fill(fill) => {
+                        markout::record_fill(&mut s, fill);
+                        limits::record_fill(&mut s, fill);
+                        strategy.on_fill(fill, &mut s)
+                    }
+                    StrategyEvent::Order(order_event) => strategy.on_order_event(order_event, &mut s),
+                };
+                plan_commands(action, &yes_token, &mut s)
+            }
+            update = market_rx.recv() => {
+                let Some(update) = update else {
+                    break;
+                };
+                let mut s = state.lock().await;
+                // Due markouts see the mid from before this update
+                markout::sample(&mut s);
+                // Update market data
+                s.last_prices.insert(
+                    update.asset_id.clone(),
+                    (update.best_bid, update.best_ask, update.ts),
+                );
+                let mid = (update.best_bid + update.best_ask) / 2.0;
+                volatility::observe_mid(&mut s, &update.asset_id, mid);
+                let Some(yes_token) = s.yes_token_of(&update.asset_id).map(str::to_string) else {
+                    continue;
+                };
+                let action = if circuit_breaker::observe(&mut s, &yes_token, &update) {
+                    StrategyAction::CancelAll
+                } else if s.market_config(&yes_token).enabled {
+                    strategy_for(&mut strategies, &mut s, &yes_token).on_market_update(&update, &mut s)
+                } else {
+                    pull_quotes(&mut s, &yes_token)
+                };
+                plan_commands(action, &yes_token, &mut s)
+            }
+        };
         if is_shutting_down(&state).await {
             break;
         }
-
-        // Update market data
-        {
-            let mut s = state.lock().await;
-            s.last_prices.insert(
-                update.asset_id.clone(),
-                (update.best_bid, update.best_ask, update.ts),
-            );
+        for cmd in commands {
+            if cmd_tx.send(cmd).await.is_err() {
+                return;
+            }
         }
-
-        // Execute sophisticated market-making algorithm
-        execute_market_making_strategy(&update, &cmd_tx, &state).await;
     }
 }
 
-// -------------------- Market Making Strategy --------------------
-pub async fn execute_market_making_strategy(
-    update: &MarketUpdate,
-    cmd_tx: &mpsc::Sender<BotCommand>,
-    state: &Arc<Mutex<AppState>>,
-) {
-    // -------------------- 0. Configuration --------------------
-    // These values can be moved to environment variables for easier tuning.
-    const BASE_ORDER_SIZE: f64 = 500.0; // The base size of orders to place (e.g., $10).
-    const EDGE_PCT: f64 = 0.02; // Quote 2% away from current best bid/ask to avoid immediate fills on wide spreads.
-    const AGGRESSIVE_SKEW: f64 = 0.01; // Only applied when imbalance dollars exceed the threshold.
-
-    // -------------------- 1. Get State & Perform Risk Checks --------------------
-    let (
-        yes_token,
-        // no_token,
-        inventory_yes,
-        // inventory_no,
-        max_imbalance,
-        max_position,
-        open_orders,
-    ) = {
-        let s = state.lock().await;
-        if s.risk_paused || s.shutting_down {
-            return;
+/// The market's strategy instance, built on first use and rebuilt when its config names
+/// another strategy. Names are validated with the config; one that still slips through is
+/// logged and the market runs the edge strategy instead.
+fn strategy_for<'a>(
+    strategies: &'a mut HashMap<String, Box<dyn Strategy>>,
+    s: &mut AppState,
+    yes_token: &str,
+) -> &'a mut Box<dyn Strategy> {
+    let name = s.market_config(yes_token).strategy.clone();
+    let current = strategies.get(yes_token).map(|strategy| strategy.name());
+    if current != Some(name.as_str()) {
+        let next = match build_strategy(&name) {
+            Some(next) => Some(next),
+            None if current == Some(STRATEGY_EDGE) => None,
+            None => {
+                logger::logln(format!(
+                    "Strategy: Unknown strategy '{}' for {}; using {}",
+                    name, yes_token, STRATEGY_EDGE
+                ));
+                build_strategy(STRATEGY_EDGE)
+            }
+        };
+        if let Some(next) = next {
+            match current {
+                Some(current) => logger::logln(format!(
+                    "Strategy: Config switched {} strategy {} -> {}",
+                    yes_token,
+                    current,
+                    next.name()
+                )),
+                None => logger::logln(format!(
+                    "Strategy: Using {} strategy for {}",
+                    next.name(),
+                    yes_token
+                )),
+            }
+            strategies.insert(yes_token.to_string(), next);
+            s.last_mid_bucket.remove(yes_token);
         }
+    }
+    strategies
+        .entry(yes_token.to_string())
+        .or_insert_with(|| Box::new(EdgeMarketMaker::new()))
+}
 
-        // Get the explicit YES token from state
-        let yes_token = s.yes_token.clone().unwrap_or_default();
-        if yes_token.is_empty() {
-            return;
-        }
-        // let no_token = s.token_pairs.get(&yes_token).cloned().unwrap_or_default();
-        // if no_token.is_empty() {
-        //     return;
-        // }
+/// Disabled market: want nothing on the book, and re-quote from scratch once re-enabled
+fn pull_quotes(s: &mut AppState, yes_token: &str) -> StrategyAction {
+    s.last_mid_bucket.remove(yes_token);
+    StrategyAction::Quote(Vec::new())
+}
 
-        let inventory_yes = s.inventory.get(&yes_token).copied().unwrap();
-        // let inventory_no = s.inventory.get(&no_token).copied().unwrap();
+/// A quiet market whose orders are about to lapse (GTD safety net) re-posts them through the
+/// quote diff: `Keep` becomes its resting orders, of which the diff replaces only those due.
+/// Paused markets are left alone.
+fn renew_due(action: StrategyAction, yes_token: &str, s: &AppState) -> StrategyAction {
+    let (StrategyAction::Keep, Some(market)) = (&action, s.markets.get(yes_token)) else {
+        return action;
+    };
+    if pause::is_paused(s, yes_token) {
+        return action;
+    }
+    let resting: Vec<(&OrderId, &Order)> = s
+        .my_open_orders
+        .iter()
+        .filter(|(id, o)| {
+            market.tokens().contains(&&o.asset_id) && !s.cancel_pending.contains_key(*id)
+        })
+        .collect();
+    if !resting.iter().any(|(id, _)| heartbeat::renewal_due(s, id)) {
+        return action;
+    }
+    StrategyAction::Quote(
+        resting
+            .into_iter()
+            .map(|(_, o)| Order {
+                id: None,
+                ..o.clone()
+            })
+            .collect(),
+    )
+}
 
-        let open_orders: Vec<(OrderId, String)> = s
+/// Markets under a limit breach: `Pause` wants nothing on the book, `ReduceOnly` keeps only
+/// the quotes (or, for `Keep`, the resting orders) that shrink the unpaired position.
+/// `Flatten` filters the same way; the unwind it starts quotes only reducing sells.
+fn apply_limits(action: StrategyAction, yes_token: &str, s: &AppState) -> StrategyAction {
+    let Some(restriction) = limits::restriction(s, yes_token) else {
+        return action;
+    };
+    let Some(market) = s.markets.get(yes_token) else {
+        return action;
+    };
+    let quotes = match (restriction, action) {
+        (_, StrategyAction::CancelAll) => return StrategyAction::CancelAll,
+        (BreachAction::Pause, _) => return StrategyAction::Quote(Vec::new()),
+        (BreachAction::ReduceOnly | BreachAction::Flatten, StrategyAction::Quote(quotes)) => quotes,
+        (BreachAction::ReduceOnly | BreachAction::Flatten, StrategyAction::Keep) => s
             .my_open_orders
             .iter()
-            .filter(|(_, order)| order.asset_id == yes_token)
-            // .filter(|(_, order)| order.asset_id == yes_token || order.asset_id == no_token)
-            .map(|(id, order)| (id.clone(), order.asset_id.clone()))
-            .collect();
-
-        (
-            yes_token,
-            // no_token,
-            inventory_yes,
-            // inventory_no,
-            s.max_inventory_imbalance,
-            s.max_position_size,
-            open_orders,
-        )
+            .filter(|(id, o)| {
+                market.tokens().contains(&&o.asset_id) && !s.cancel_pending.contains_key(*id)
+            })
+            .map(|(_, o)| o.clone())
+            .collect(),
     };
+    StrategyAction::Quote(
+        quotes
+            .into_iter()
+            .filter(|o| limits::reduces(s, yes_token, o))
+            .collect(),
+    )
+}
 
-    // Risk Check 1: Pause if prices are too extreme.
-    if update.best_ask >= 0.98
-        || update.best_ask <= 0.02
-        || update.best_bid <= 0.02
-        || update.best_bid >= 0.98
-    {
-        logger::logln(
-            "Strategy: Extreme prices detected. Pausing and canceling all orders.".to_string(),
-        );
-        let mut s = state.lock().await;
-        s.risk_paused = true;
-        let _ = cmd_tx.send(BotCommand::CancelAll).await;
-        return;
-    }
-
-    // Risk Check 2: Check position size and inventory imbalance
-    // Convert token quantities to dollar values using current market prices
-    let current_mid_price = (update.best_bid + update.best_ask) / 2.0;
-    let inventory_yes_dollars = inventory_yes * current_mid_price;
-    let open_orders_size = open_orders.len();
-    // let inventory_no_dollars = inventory_no * (1.0 - current_mid_price);
-
-    // let inventory_imbalance_dollars = (inventory_yes_dollars - inventory_no_dollars).abs();
-    // let total_position_dollars = inventory_yes_dollars + inventory_no_dollars;
-
-    // // Check if we should place orders (position size check)
-    // let should_place_orders = total_position_dollars <= max_position;
-
-    // if !should_place_orders {
-    //     println!(
-    //         "Strategy: Max position size exceeded (${:.2} > ${:.2}). Will skip order placement but continue monitoring.",
-    //         total_position_dollars, max_position
-    //     );
-    // }
-
-    // // Only apply aggressive skew when the dollar imbalance breaches the threshold; otherwise no skew.
-    // let signed_imbalance_dollars = inventory_yes_dollars - inventory_no_dollars;
-    // let apply_skew = inventory_imbalance_dollars > max_imbalance;
-
-    // -------------------- 2. Conditional cancel based on mid-price bucket OR skew requirements --------------------
-    // Compute mid and a coarse bucket; only re-quote if the bucket changed.
-    let mid_bucket = (current_mid_price * 100.0).ceil() as i32; // 1-cent buckets
-    let should_requote_price = {
-        let mut s = state.lock().await;
-        let prev = s
-            .last_mid_bucket
-            .insert(update.asset_id.clone(), mid_bucket);
-        match prev {
-            Some(last) => last != mid_bucket,
-            None => true,
+// -------------------- Quote Diffing --------------------
+/// Translate one market's strategy action into commands; orders of other markets are never
+/// touched. For `Quote`, resting orders that match a desired quote are kept, the rest are
+/// canceled, and only missing quotes are created. Orders whose cancel is unconfirmed are
+/// left to reconciliation; quotes already sent but not yet placed count as present so they
+/// are not sent twice. Replacements are then throttled per token by `[requote]`, and quotes
+/// past their max age are replaced even when unchanged. `CancelAll` cancels the market's
+/// resting orders at once. Orders about to lapse are replaced like expired quotes. An unwind
+/// replaces the strategy's quotes, then limit breaches are applied.
+fn plan_commands(action: StrategyAction, yes_token: &str, s: &mut AppState) -> Vec<BotCommand> {
+    let Some(market) = s.markets.get(yes_token).cloned() else {
+        return Vec::new();
+    };
+    let action = unwind::apply(action, yes_token, s);
+    let action = apply_limits(action, yes_token, s);
+    let ours = |order: &Order| market.tokens().contains(&&order.asset_id);
+    let mut desired = match action {
+        StrategyAction::Keep => return Vec::new(),
+        StrategyAction::CancelAll => {
+            s.pending_creates.retain(|o| !ours(o));
+            Vec::new()
         }
+        StrategyAction::Quote(desired) => desired,
     };
 
-    // Also requote if we need to apply skew (inventory imbalance exceeded threshold)
-    // let should_requote_skew = apply_skew;
-
-    // Requote if either price moved OR skew is needed
-    // || should_requote_skew;
+    let mut cancels = Vec::new();
+    for (id, order) in &s.my_open_orders {
+        if s.cancel_pending.contains_key(id) || !ours(order) {
+            continue;
+        }
+        let matched = (!requote::expired(s, &order.asset_id) && !heartbeat::renewal_due(s, id))
+            .then(|| desired.iter().position(|d| d.same_quote(order)))
+            .flatten();
+        match matched {
+            Some(pos) => {
+                desired.remove(pos);
+            }
+            None => cancels.push((id.clone(), order.clone())),
+        }
+    }
+    for in_flight in &s.pending_creates {
+        if let Some(pos) = desired.iter().position(|d| d.same_quote(in_flight)) {
+            desired.remove(pos);
+        }
+    }
+    // Held back only until the lifetime passes: re-evaluate on the next update
+    if requote::hold(s, &mut cancels, &mut desired) {
+        s.last_mid_bucket.remove(yes_token);
+    }
 
-    if should_requote_price {
-        logger::logln(format!(
-            "Strategy: Price moved. Canceling orders for requote if open orders. Current open orders={} and current mid bucket={}",
-            open_orders_size, mid_bucket
-        ));
-        if open_orders_size > 0 {
-            logger::logln("Strategy: Price moved. Canceling orders for requote.".to_string());
-            let _ = cmd_tx.send(BotCommand::CancelAll).await;
+    let mut commands = Vec::new();
+    for (id, _) in cancels {
+        if heartbeat::renewal_due(s, &id) {
+            logger::logln(format!("Strategy: Renewing order {} before its expiry", id));
         }
-    } else {
-        // No material move; keep existing quotes
-        let last_mid_bucket = {
-            let s = state.lock().await;
-            s.last_mid_bucket.get(&update.asset_id).copied()
-        };
+        // Renewed once: a canceled order is not due again while its cancel is in flight
+        s.order_expiries.remove(&id);
+        commands.push(BotCommand::Cancel(id));
+    }
+    for order in desired {
         logger::logln(format!(
-            "Strategy: Price not moved. Returning. Current open orders={} and current mid bucket={}, best bid={:.2}, best ask={:.2} last mid bucket={:?}",
-            open_orders_size, mid_bucket, update.best_bid, update.best_ask, last_mid_bucket
+            "Strategy: Quoting {:?} {} @ {:.4} x {:.2}",
+            order.side, order.asset_id, order.price, order.size
         ));
-        return;
+        requote::mark_quoted(s, &order.asset_id);
+        s.pending_creates.push(order.clone());
+        commands.push(BotCommand::Create(order));
     }
+    commands
+}
 
-    // -------------------- 3. Calculate Quotes anchored to best bid/ask with asymmetrical skew --------------------
-    // Base quotes: 10% away from current best bid/ask to avoid immediate fills on wide spreads.
-    let our_bid_price = (update.best_bid * (1.0 - EDGE_PCT)).max(0.01);
-    let our_ask_price = (update.best_ask + EDGE_PCT * update.best_ask).min(0.99);
+// -------------------- Helper Functions --------------------
+async fn is_shutting_down(state: &Arc<Mutex<AppState>>) -> bool {
+    state.lock().await.shutting_down
+}
 
-    // Quantize to cents: floor bid, ceil ask
-    let our_bid_price = ((our_bid_price * 100.0).ceil() / 100.0).max(0.01);
-    let our_ask_price = ((our_ask_price * 100.0).floor() / 100.0).min(0.99);
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::test_support::{add_market, order, rest};
+    use crate::modules::types::{now_millis, Side};
 
-    logger::logln(format!(
-        "Strategy: Calculated quotes: bid={:.2}, ask={:.2} and best bid={:.2}, best ask={:.2} should requote={} open orders={} yes_token={}",
-        our_bid_price, our_ask_price, update.best_bid, update.best_ask, should_requote_price, open_orders_size, yes_token.clone()
-    ));
+    #[test]
+    fn only_orders_about_to_lapse_are_renewed_and_tracked() {
+        let mut s = AppState::default();
+        let no_token = add_market(&mut s, "yes", 0.49, 0.51);
+        let now_secs = now_millis() / 1000;
+        rest(&mut s, "due", order("yes", Side::Buy, 0.48, 10.0));
+        rest(&mut s, "fresh", order(&no_token, Side::Sell, 0.53, 10.0));
+        s.order_expiries.insert("due".to_string(), now_secs + 5);
+        s.order_expiries.insert("fresh".to_string(), now_secs + 300);
 
-    // Apply aggressive, asymmetrical skew only when imbalance exceeds threshold.
-    // if apply_skew {
-    //     println!(
-    //         "Strategy: High inventory imbalance (${:.2} > ${:.2}). Applying asymmetrical skew.",
-    //         inventory_imbalance_dollars, max_imbalance
-    //     );
-    //     if signed_imbalance_dollars > 0.0 {
-    //         // Long YES: sell YES faster (lower ask), discourage buying more (lower bid)
-    //         let ask_skew = AGGRESSIVE_SKEW;
-    //         let bid_skew = AGGRESSIVE_SKEW;
-    //         our_ask_price = (base_ask - ask_skew).clamp(0.01, 0.99);
-    //         our_bid_price = (base_bid - bid_skew).clamp(0.01, 0.99);
-    //     } else {
-    //         // Short YES: buy YES faster (raise bid), discourage selling more (raise ask)
-    //         let bid_skew = AGGRESSIVE_SKEW;
-    //         let ask_skew = AGGRESSIVE_SKEW;
-    //         our_bid_price = (base_bid + bid_skew).clamp(0.01, 0.99);
-    //         our_ask_price = (base_ask + ask_skew).clamp(0.01, 0.99);
-    //     }
-    // }
+        let action = renew_due(StrategyAction::Keep, "yes", &s);
+        let commands = plan_commands(action, "yes", &mut s);
+        assert_eq!(commands.len(), 2);
+        assert!(matches!(&commands[0], BotCommand::Cancel(id) if id == "due"));
+        assert!(
+            matches!(&commands[1], BotCommand::Create(o) if o.same_quote(&s.my_open_orders["due"]))
+        );
+        assert_eq!(s.pending_creates.len(), 1);
 
-    // // Ensure our bid is not higher than our ask after all adjustments.
-    // if our_bid_price >= our_ask_price {
-    //     println!("Strategy: Calculated bid is higher than ask. Skipping quote placement.");
-    //     return;
-    // }
+        // Renewed once while the cancel is in flight
+        let action = renew_due(StrategyAction::Keep, "yes", &s);
+        assert!(plan_commands(action, "yes", &mut s).is_empty());
 
-    // -------------------- 4. Place New Two-Sided Quotes (if position allows) --------------------
-    if should_requote_price || open_orders_size == 0 {
-        // Action 1: Place the BUY order for the YES token.
-        let _ = cmd_tx
-            .send(BotCommand::Create(Order {
-                id: None,
-                asset_id:
-                    "48525272152376477814181507566364840492981593695809374031340296121651261876072"
-                        .to_string(), //yes_token.clone(),
-                side: Side::Sell,
-                price: 1.0 - our_bid_price,
-                size: BASE_ORDER_SIZE,
-            }))
-            .await;
+        // A paused market is left to lapse
+        s.order_expiries.insert("fresh".to_string(), now_secs + 5);
+        s.risk_paused = true;
+        assert!(matches!(
+            renew_due(StrategyAction::Keep, "yes", &s),
+            StrategyAction::Keep
+        ));
+    }
 
-        let _ = cmd_tx
-            .send(BotCommand::Create(Order {
-                id: None,
-                asset_id: yes_token.clone(),
-                side: Side::Sell,
-                price: our_ask_price,
-                size: BASE_ORDER_SIZE,
-            }))
-            .await;
-    } else {
-        logger::logln(
-            "Strategy: Position limit reached. Skew calculations completed but no orders placed."
-                .to_string(),
+    #[test]
+    fn unknown_strategy_name_falls_back_to_edge() {
+        let mut s = AppState::default();
+        add_market(&mut s, "yes", 0.49, 0.51);
+        s.config.default.strategy = "missing".to_string();
+        let mut strategies = HashMap::new();
+        assert_eq!(
+            strategy_for(&mut strategies, &mut s, "yes").name(),
+            STRATEGY_EDGE
+        );
+        assert_eq!(
+            strategy_for(&mut strategies, &mut s, "yes").name(),
+            STRATEGY_EDGE
         );
     }
-
-    // Action 2: Place the SELL order for the YES token.
-    // This is done by placing a BUY order for the complementary NO token.
-    // The price is calculated as 1.0 - our desired SELL price for YES.
-    // let no_token_buy_price = 1.0 - our_ask_price;
-    // let _ = cmd_tx
-    //     .send(BotCommand::Create(Order {
-    //         id: None,
-    //         asset_id: no_token,
-    //         side: Side::Buy,
-    //         price: no_token_buy_price,
-    //         size: BASE_ORDER_SIZE,
-    //     }))
-    //     .await;
-}
-
-// -------------------- Helper Functions --------------------
-async fn is_shutting_down(state: &Arc<Mutex<AppState>>) -> bool {
-    state.lock().await.shutting_down
 }
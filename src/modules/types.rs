@@ -1,5 +1,19 @@
+use crate::modules::config::{BotConfig, MarketConfig};
+use crate::modules::heartbeat::Component;
+use crate::modules::limits::{Breach, DailyPnl, LimitScope};
+use crate::modules::market_manager::MarketSpec;
+use crate::modules::markout::MarkoutTracker;
+use crate::modules::metrics::LatencyStats;
+use crate::modules::order_book::DepthBook;
+use crate::modules::pause::{Pause, PauseReason};
+use crate::modules::rewards::RewardParams;
+use crate::modules::unwind::Unwind;
+use crate::modules::volatility::VolEstimator;
+use crate::modules::wind_down::MarketSchedule;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
+use std::time::Instant;
+use tokio::sync::mpsc;
 
 // -------------------- Domain Types --------------------
 // String constants to avoid typos in comparisons/assignments
@@ -21,7 +35,7 @@ pub const EVT_TICK_SIZE_CHANGE: &str = "tick_size_change";
 pub const EVT_LAST_TRADE_PRICE: &str = "last_trade_price";
 pub type OrderId = String;
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Side {
     Buy,
     Sell,
@@ -36,6 +50,34 @@ pub struct Order {
     pub size: f64,
 }
 
+const QUOTE_EPSILON: f64 = 1e-9;
+
+impl Order {
+    /// Same resting quote, ignoring the order id
+    pub fn same_quote(&self, other: &Order) -> bool {
+        self.asset_id == other.asset_id
+            && self.side == other.side
+            && (self.price - other.price).abs() < QUOTE_EPSILON
+            && (self.size - other.size).abs() < QUOTE_EPSILON
+    }
+}
+
+/// What we know about an order whose cancel has not been confirmed
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CancelState {
+    /// Cancel request failed; the order is assumed live until reconciled
+    Pending,
+    /// The exchange could not tell us the order's status either
+    Unknown,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PendingCancel {
+    pub state: CancelState,
+    pub attempts: u32,
+    pub last_attempt_ms: i64,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct MarketUpdate {
     pub asset_id: String,
@@ -54,11 +96,47 @@ pub enum BotCommand {
     Cancel(OrderId),
     /// Cancel all open orders (risk management)
     CancelAll,
-    /// Update inventory when an order gets filled (from external notifications)
-    #[allow(dead_code)]
+    /// Update inventory when an order gets filled (from external notifications or paper fills)
     OrderFilled(OrderId, String, Side, f64, f64), // order_id, token_id, side, price, size
-    /// Graceful shutdown signal with chain context (condition_id, proxy_wallet)
-    Shutdown(String, String),
+    /// Merge paired YES/NO inventory back into USDC while running (reserved in `merge_reserved`);
+    /// runs on its own task so the command loop is not held up by the transaction. The market
+    /// travels with it so the result is settled even if the market stops meanwhile.
+    Merge(MarketSpec, String, i64), // market, proxy_wallet, shares
+    /// Split USDC into YES/NO pairs to cover the provisioning shortfall (tracked in `split_pending`);
+    /// runs on its own task like `Merge`
+    Split(MarketSpec, String, i64), // market, proxy_wallet, shares
+    /// Graceful shutdown signal; free pairs of every running market are merged through the proxy wallet
+    Shutdown(String), // proxy_wallet
+}
+
+/// A command stamped with the moment its producer emitted it
+#[derive(Debug)]
+pub struct TimedCommand {
+    pub cmd: BotCommand,
+    pub emitted_at: Instant,
+}
+
+/// Sending half of the command channel; stamps every command as it is sent
+#[derive(Clone, Debug)]
+pub struct CommandSender(mpsc::Sender<TimedCommand>);
+
+impl CommandSender {
+    pub async fn send(&self, cmd: BotCommand) -> Result<(), mpsc::error::SendError<BotCommand>> {
+        let timed = TimedCommand {
+            cmd,
+            emitted_at: Instant::now(),
+        };
+        self.0
+            .send(timed)
+            .await
+            .map_err(|e| mpsc::error::SendError(e.0.cmd))
+    }
+}
+
+/// Command channel (Trading Logic and helpers -> Order Execution)
+pub fn command_channel(capacity: usize) -> (CommandSender, mpsc::Receiver<TimedCommand>) {
+    let (tx, rx) = mpsc::channel(capacity);
+    (CommandSender(tx), rx)
 }
 
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
@@ -68,14 +146,150 @@ pub struct AppState {
     // Inventory management
     pub inventory: HashMap<String, f64>, // token_id -> quantity owned
     pub token_pairs: HashMap<String, String>, // yes_token -> no_token mapping
-    pub yes_token: Option<String>,       // Explicit YES token ID for reference
+    #[serde(skip)]
+    pub markets: HashMap<String, MarketSpec>, // yes_token -> running market, kept by the market manager
     // Risk management
-    pub risk_paused: bool,
+    pub risk_paused: bool, // set while any pause reason is active
     pub shutting_down: bool,
-    pub max_inventory_imbalance: f64, // max allowed inventory difference between yes/no
-    pub max_position_size: f64,       // max total position size per market
+    #[serde(skip)]
+    pub unwind_all: bool, // SIGUSR1 asked every market to unwind; SIGUSR2 clears it
+    #[serde(skip)]
+    pub unwinds: HashMap<String, Unwind>, // yes_token -> running unwind
+    #[serde(skip)]
+    pub pauses: HashMap<PauseReason, Pause>, // active pause reasons and their recovery progress
+    #[serde(skip)]
+    pub breaches: HashMap<LimitScope, Breach>, // limit scopes over their limits
+    #[serde(default)]
+    pub daily_pnl: HashMap<String, DailyPnl>, // yes_token -> today's cash flow and opening value
+    #[serde(skip)]
+    pub event_groups: HashMap<String, String>, // yes_token -> neg-risk event id from metadata
+    #[serde(skip)]
+    pub config: BotConfig, // strategy and risk parameters, hot-reloaded from the config file
+    #[serde(default)]
+    pub tick_sizes: HashMap<String, f64>, // asset_id -> tick size reported by the market WS
     // Quoting management: track last mid-price bucket to reduce cancels
     pub last_mid_bucket: HashMap<String, i32>, // asset_id -> ceil(mid*100) bucket (bucket is an integer between 0 and 100)
+    #[serde(skip)]
+    pub quoted_at: HashMap<String, Instant>, // asset_id -> when its quotes were last placed, for the requote throttle
+    #[serde(skip)]
+    pub queue_ahead: HashMap<OrderId, f64>, // order_id -> estimated shares ahead of it at its price level
+    // Dead-man switch
+    #[serde(default)]
+    pub order_ttl_secs: u64, // GTD lifetime beyond the exchange's 60s buffer; 0 = GTC
+    #[serde(default)]
+    pub order_expiries: HashMap<OrderId, i64>, // order_id -> GTD expiration (unix seconds)
+    #[serde(skip)]
+    pub heartbeats: HashMap<Component, i64>, // component -> last beat (ms)
+    #[serde(skip)]
+    pub dead_man_tripped: bool,
+    // Unconfirmed cancels: orders stay in my_open_orders until the exchange confirms them gone
+    #[serde(default)]
+    pub cancel_pending: HashMap<OrderId, PendingCancel>,
+    #[serde(default)]
+    pub cancel_all_pending: bool, // a CancelAll failed; orders we don't know about may be live
+    #[serde(skip)]
+    pub latency: LatencyStats, // order ack / round-trip latency per endpoint
+    // Strategy quotes sent as Create but not yet resting or rejected
+    #[serde(skip)]
+    pub pending_creates: Vec<Order>,
+    #[serde(skip)]
+    pub volatility: HashMap<String, VolEstimator>, // asset_id -> realized volatility from mids and trades
+    #[serde(skip)]
+    pub reference_mids: HashMap<String, VecDeque<(Instant, f64)>>, // asset_id -> recent mids for the circuit breaker
+    #[serde(skip)]
+    pub merge_reserved: HashMap<String, f64>, // yes_token -> pairs of a merge in flight; excluded from sell quotes
+    #[serde(skip)]
+    pub split_pending: HashMap<String, f64>, // yes_token -> pairs of a split in flight
+    #[serde(skip)]
+    pub markouts: MarkoutTracker, // fill markouts per market and side
+    #[serde(skip)]
+    pub schedules: HashMap<String, MarketSchedule>, // yes_token -> end date / game start from metadata
+    #[serde(skip)]
+    pub books: HashMap<String, DepthBook>, // asset_id -> full depth from the market WS
+    #[serde(skip)]
+    pub reward_params: HashMap<String, RewardParams>, // yes_token -> liquidity reward terms from metadata
+}
+
+impl AppState {
+    /// YES token of the running market that `asset_id` (YES or NO token) belongs to
+    pub fn yes_token_of(&self, asset_id: &str) -> Option<&str> {
+        if let Some((yes_token, _)) = self.markets.get_key_value(asset_id) {
+            return Some(yes_token);
+        }
+        let yes_token = self.token_pairs.get(asset_id)?;
+        self.markets.contains_key(yes_token).then_some(yes_token)
+    }
+
+    /// Every outcome token of the running markets, for feed subscriptions
+    pub fn market_tokens(&self) -> Vec<String> {
+        let mut tokens: Vec<String> = self
+            .markets
+            .values()
+            .flat_map(|m| m.tokens().map(|t| t.clone()))
+            .collect();
+        tokens.sort();
+        tokens
+    }
+
+    /// Pairs of `yes_token`'s market being merged right now
+    pub fn merge_reserved(&self, yes_token: &str) -> f64 {
+        self.merge_reserved.get(yes_token).copied().unwrap_or(0.0)
+    }
+
+    /// Config for the market that `asset_id` (YES or NO token) belongs to
+    pub fn market_config(&self, asset_id: &str) -> &MarketConfig {
+        self.config.market(asset_id, self.token_pairs.get(asset_id))
+    }
+
+    /// Tick for `asset_id`: the exchange's latest if the market follows it, else the configured one
+    pub fn tick_size(&self, asset_id: &str) -> f64 {
+        let tick = &self.market_config(asset_id).tick;
+        if tick.follow_exchange {
+            if let Some(size) = self.tick_sizes.get(asset_id) {
+                return *size;
+            }
+        }
+        tick.default_size
+    }
+
+    /// New quotes are blocked while any cancel is unconfirmed
+    pub fn quoting_blocked(&self) -> bool {
+        self.cancel_all_pending || !self.cancel_pending.is_empty()
+    }
+
+    /// Mark an order's cancel as unconfirmed so it is retried and reconciled
+    pub fn mark_cancel_pending(&mut self, order_id: &str, state: CancelState, now_ms: i64) {
+        let entry = self
+            .cancel_pending
+            .entry(order_id.to_string())
+            .or_insert(PendingCancel {
+                state,
+                attempts: 0,
+                last_attempt_ms: now_ms,
+            });
+        entry.state = state;
+        entry.attempts += 1;
+        entry.last_attempt_ms = now_ms;
+    }
+
+    /// Forget an order entirely once the exchange confirms it is gone
+    pub fn forget_order(&mut self, order_id: &str) {
+        self.my_open_orders.remove(order_id);
+        self.order_expiries.remove(order_id);
+        self.cancel_pending.remove(order_id);
+        self.latency.forget(order_id);
+    }
+
+    /// A Create was placed, rejected or failed; it is no longer in flight
+    pub fn take_pending_create(&mut self, order: &Order) {
+        if let Some(pos) = self
+            .pending_creates
+            .iter()
+            .position(|p| p.same_quote(order))
+        {
+            self.pending_creates.remove(pos);
+        }
+    }
 }
 
 // -------------------- WebSocket Message Types --------------------
@@ -197,6 +411,15 @@ pub struct UserOrderMessage {
 }
 
 // -------------------- Helper Functions --------------------
+/// Wall-clock unix time in milliseconds
+pub fn now_millis() -> i64 {
+    use std::time::{SystemTime, UNIX_EPOCH};
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or(0)
+}
+
 pub fn side_str(side: &Side) -> &'static str {
     match side {
         Side::Buy => "BOUGHT",
@@ -252,3 +475,25 @@ pub struct PositionApiResponse {
     pub endDate: String, // could also be chrono::DateTime if ISO 8601
     pub negativeRisk: bool,
 }
+
+/// The fields we use from the Gamma API's /markets
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GammaMarketResponse {
+    pub condition_id: String,
+    pub end_date: Option<String>,        // ISO 8601
+    pub game_start_time: Option<String>, // "2025-01-01 20:00:00+00" on sports markets
+    pub rewards_min_size: Option<f64>,   // shares
+    pub rewards_max_spread: Option<f64>, // cents from the midpoint
+    #[serde(default)]
+    pub clob_rewards: Vec<GammaClobReward>,
+    pub neg_risk: Option<bool>,
+    #[serde(rename = "negRiskMarketID")]
+    pub neg_risk_market_id: Option<String>, // shared by every market of a neg-risk event
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GammaClobReward {
+    pub rewards_daily_rate: f64, // USDC per day across all makers
+}
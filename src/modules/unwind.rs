@@ -0,0 +1,290 @@
+use crate::modules::limits::{self, BreachAction};
+use crate::modules::logger;
+use crate::modules::markout;
+use crate::modules::pause;
+use crate::modules::strategy::StrategyAction;
+use crate::modules::types::{AppState, Order, Side};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::sync::Mutex;
+
+// -------------------- Unwind Defaults --------------------
+const DEFAULT_PASSIVE_SECS: u64 = 300;
+const DEFAULT_DEADLINE_SECS: u64 = 1800;
+const DEFAULT_MAX_SLIPPAGE_CENTS: f64 = 5.0;
+const REPORT_SECS: u64 = 30; // progress is logged this often
+const SIZE_DECIMALS: f64 = 100.0; // sizes are rounded down to 0.01 shares
+
+/// `unwind` in the config
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct UnwindConfig {
+    pub enabled: bool,           // true flattens the market instead of quoting it
+    pub passive_secs: u64,       // sell at the best ask this long, then escalate
+    pub deadline_secs: u64,      // the price limit is reached this long after the start
+    pub max_slippage_cents: f64, // price limit: this far below the token's mid at the start
+}
+
+impl Default for UnwindConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            passive_secs: DEFAULT_PASSIVE_SECS,
+            deadline_secs: DEFAULT_DEADLINE_SECS,
+            max_slippage_cents: DEFAULT_MAX_SLIPPAGE_CENTS,
+        }
+    }
+}
+
+/// Where an unwind is
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UnwindPhase {
+    /// Resting at the best ask
+    Passive,
+    /// Stepping the price from the best ask down to the limit, crossing the spread on the way
+    Escalating,
+    /// Deadline passed; resting at the price limit
+    AtLimit,
+    /// Less than the minimum order size left unpaired
+    Flat,
+}
+
+/// A running unwind of one market
+#[derive(Clone, Debug)]
+pub struct Unwind {
+    pub started: Instant,
+    pub start_shares: f64, // unpaired shares when it started
+    pub start_yes_mid: f64,
+    pub phase: UnwindPhase,
+    escalate_from: Option<(String, f64)>, // token and passive price when escalation began
+    last_report: Instant,
+}
+
+/// Whether `yes_token`'s market should unwind: its config asks for it, a SIGUSR1 did, or a
+/// limit breach covering it has the `flatten` action
+fn requested(s: &AppState, yes_token: &str) -> bool {
+    s.unwind_all
+        || s.market_config(yes_token).unwind.enabled
+        || limits::restriction(s, yes_token) == Some(BreachAction::Flatten)
+}
+
+/// The token we hold more of, and by how many shares
+fn excess(s: &AppState, yes_token: &str) -> Option<(String, f64)> {
+    let no_token = s.token_pairs.get(yes_token)?;
+    let held = |token: &str| s.inventory.get(token).copied().unwrap_or(0.0);
+    let yes_excess = held(yes_token) - held(no_token);
+    Some(if yes_excess >= 0.0 {
+        (yes_token.to_string(), yes_excess)
+    } else {
+        (no_token.clone(), -yes_excess)
+    })
+}
+
+// -------------------- Progress --------------------
+/// Start and stop unwinds as requested, move them through their phases and report progress;
+/// called once a second by the trading task
+pub fn review(s: &mut AppState) {
+    let markets: Vec<String> = s.markets.keys().cloned().collect();
+    s.unwinds.retain(|yes_token, _| markets.contains(yes_token));
+    for yes_token in markets {
+        if !requested(s, &yes_token) {
+            if s.unwinds.remove(&yes_token).is_some() {
+                logger::logln(format!(
+                    "Unwind: Stopped for {}; quoting resumes",
+                    yes_token
+                ));
+                s.last_mid_bucket.remove(&yes_token);
+            }
+            continue;
+        }
+        let Some((token, shares)) = excess(s, &yes_token) else {
+            continue;
+        };
+        let config = s.market_config(&yes_token).unwind.clone();
+        let min_size = s.market_config(&yes_token).risk.min_order_size;
+        if !s.unwinds.contains_key(&yes_token) {
+            // Wait for a mid: the price limit is measured from it
+            let Some(start_yes_mid) = markout::yes_mid(s, &yes_token) else {
+                continue;
+            };
+            logger::logln(format!(
+                "Unwind: Started for {}: {:.2} unpaired shares of {}; passive for {}s, then down to {:.1}c below the mid by {}s",
+                yes_token,
+                shares,
+                token,
+                config.passive_secs,
+                config.max_slippage_cents,
+                config.deadline_secs
+            ));
+            s.unwinds.insert(
+                yes_token.clone(),
+                Unwind {
+                    started: Instant::now(),
+                    start_shares: shares,
+                    start_yes_mid,
+                    phase: UnwindPhase::Passive,
+                    escalate_from: None,
+                    last_report: Instant::now(),
+                },
+            );
+        }
+        // Escalation steps down from where the passive order stood, not from our own asks
+        let passive = s
+            .unwinds
+            .get(&yes_token)
+            .and_then(|unwind| prices(s, &yes_token, unwind))
+            .map(|p| (token.clone(), p.passive));
+        let Some(unwind) = s.unwinds.get_mut(&yes_token) else {
+            continue;
+        };
+        let elapsed = unwind.started.elapsed().as_secs();
+        let phase = if shares < min_size {
+            UnwindPhase::Flat
+        } else if elapsed < config.passive_secs {
+            UnwindPhase::Passive
+        } else if elapsed < config.deadline_secs {
+            UnwindPhase::Escalating
+        } else {
+            UnwindPhase::AtLimit
+        };
+        let changed = phase != unwind.phase;
+        if changed || unwind.last_report.elapsed().as_secs() >= REPORT_SECS {
+            logger::logln(format!(
+                "Unwind: {} {:?} after {}s: {:.2} of {:.2} shares left unpaired ({}){}",
+                yes_token,
+                phase,
+                elapsed,
+                shares,
+                unwind.start_shares,
+                token,
+                if changed && phase == UnwindPhase::AtLimit {
+                    "; deadline passed, resting at the price limit"
+                } else {
+                    ""
+                }
+            ));
+            unwind.last_report = Instant::now();
+        }
+        if phase == UnwindPhase::Escalating && unwind.escalate_from.is_none() {
+            unwind.escalate_from = passive;
+        }
+        unwind.phase = phase;
+    }
+}
+
+// -------------------- Quotes --------------------
+/// An unwinding market quotes only its unwind order (nothing once flat) whatever its
+/// strategy wants; `CancelAll` still cancels everything. A paused market gets no new unwind
+/// order until the pause is lifted.
+pub fn apply(action: StrategyAction, yes_token: &str, s: &AppState) -> StrategyAction {
+    match (s.unwinds.get(yes_token), action) {
+        (None, action) => action,
+        (Some(_), StrategyAction::CancelAll) => StrategyAction::CancelAll,
+        (Some(_), _) if pause::is_paused(s, yes_token) => StrategyAction::Keep,
+        (Some(unwind), _) => {
+            StrategyAction::Quote(unwind_order(s, yes_token, unwind).into_iter().collect())
+        }
+    }
+}
+
+/// Sell prices for the unpaired excess token
+struct UnwindPrices {
+    token: String,
+    passive: f64, // the best ask, never below the limit
+    limit: f64,   // `max_slippage_cents` below the token's mid when the unwind started
+    tick: f64,
+}
+
+fn prices(s: &AppState, yes_token: &str, unwind: &Unwind) -> Option<UnwindPrices> {
+    let config = &s.market_config(yes_token).unwind;
+    let (token, _) = excess(s, yes_token)?;
+    let (bid, ask, _) = *s.last_prices.get(&token)?;
+    let tick = s.tick_size(&token);
+    let ticks_per_unit = (1.0 / tick).round();
+    let start_mid = if token == yes_token {
+        unwind.start_yes_mid
+    } else {
+        1.0 - unwind.start_yes_mid
+    };
+    let limit =
+        ((start_mid - config.max_slippage_cents / 100.0) * ticks_per_unit).ceil() / ticks_per_unit;
+    let limit = limit.max(tick);
+    Some(UnwindPrices {
+        token,
+        passive: ask.max(bid + tick).max(limit),
+        limit,
+        tick,
+    })
+}
+
+/// Sell the unpaired excess: at the best ask while passive, then at a price stepped linearly
+/// from there down to the limit by the deadline, crossing the spread once it reaches the bid.
+/// The price never goes under the limit.
+fn unwind_order(s: &AppState, yes_token: &str, unwind: &Unwind) -> Option<Order> {
+    if unwind.phase == UnwindPhase::Flat || pause::is_paused(s, yes_token) {
+        return None;
+    }
+    let config = &s.market_config(yes_token).unwind;
+    let risk = &s.market_config(yes_token).risk;
+    let (_, shares) = excess(s, yes_token)?;
+    let p = prices(s, yes_token, unwind)?;
+    let price = match unwind.phase {
+        UnwindPhase::Passive => p.passive,
+        UnwindPhase::Escalating => {
+            let from = match &unwind.escalate_from {
+                Some((token, from)) if *token == p.token => *from,
+                _ => p.passive,
+            };
+            let span = config
+                .deadline_secs
+                .saturating_sub(config.passive_secs)
+                .max(1) as f64;
+            let progress =
+                (unwind.started.elapsed().as_secs_f64() - config.passive_secs as f64) / span;
+            from - progress.clamp(0.0, 1.0) * (from - p.limit)
+        }
+        UnwindPhase::AtLimit | UnwindPhase::Flat => p.limit,
+    };
+    let ticks_per_unit = (1.0 / p.tick).round();
+    let price = ((price * ticks_per_unit).floor() / ticks_per_unit).max(p.limit);
+    let token = p.token;
+    let size =
+        (shares.min(risk.max_order_notional / price) * SIZE_DECIMALS).floor() / SIZE_DECIMALS;
+    (size >= risk.min_order_size).then_some(Order {
+        id: None,
+        asset_id: token,
+        side: Side::Sell,
+        price,
+        size,
+    })
+}
+
+// -------------------- Control Signals --------------------
+/// SIGUSR1 unwinds every running market, SIGUSR2 returns the markets whose config does not
+/// ask for an unwind to quoting
+#[cfg(unix)]
+pub async fn unwind_signal_task(state: Arc<Mutex<AppState>>) {
+    use tokio::signal::unix::{signal, SignalKind};
+    let (Ok(mut start), Ok(mut stop)) = (
+        signal(SignalKind::user_defined1()),
+        signal(SignalKind::user_defined2()),
+    ) else {
+        logger::logln("Unwind: Could not install SIGUSR1/SIGUSR2 handlers".to_string());
+        return;
+    };
+    loop {
+        let unwind_all = tokio::select! {
+            _ = start.recv() => true,
+            _ = stop.recv() => false,
+        };
+        logger::logln(format!(
+            "Unwind: {} for every market by signal",
+            if unwind_all { "Requested" } else { "Cancelled" }
+        ));
+        state.lock().await.unwind_all = unwind_all;
+    }
+}
+
+#[cfg(not(unix))]
+pub async fn unwind_signal_task(_state: Arc<Mutex<AppState>>) {}
@@ -1,4 +1,6 @@
-use crate::modules::types::{AppState, BotCommand, Side, UserWebSocketMessages};
+use crate::modules::heartbeat::{self, Component};
+use crate::modules::strategy::{Fill, OrderEvent, StrategyEvent};
+use crate::modules::types::{now_millis, AppState, CommandSender, Side, UserWebSocketMessages};
 use futures_util::{SinkExt, StreamExt};
 use serde_json::json;
 use std::sync::Arc;
@@ -7,13 +9,15 @@ use tokio::time::{sleep, Duration};
 use tokio_tungstenite::{connect_async, tungstenite::Message};
 
 const WS_USER_URL: &str = "wss://ws-subscriptions-clob.polymarket.com/ws/user";
+const PING_INTERVAL_SECS: u64 = 10;
 
 pub async fn user_ws_task(
     api_key: String,
     api_secret: String,
     api_passphrase: String,
     state: Arc<Mutex<AppState>>,
-    cmd_tx: mpsc::Sender<BotCommand>,
+    cmd_tx: CommandSender,
+    event_tx: mpsc::Sender<StrategyEvent>,
 ) {
     let mut backoff_secs = 1u64;
     loop {
@@ -38,14 +42,28 @@ pub async fn user_ws_task(
                 let _ = ws.send(Message::Text(sub.into())).await;
 
                 backoff_secs = 1;
-                while let Some(msg) = ws.next().await {
+                let mut ping = tokio::time::interval(Duration::from_secs(PING_INTERVAL_SECS));
+                loop {
+                    let msg = tokio::select! {
+                        _ = ping.tick() => {
+                            // Text PING keeps the feed (and its heartbeat) alive on quiet markets
+                            let _ = ws.send(Message::Text("PING".into())).await;
+                            continue;
+                        }
+                        msg = ws.next() => msg,
+                    };
+                    let Some(msg) = msg else {
+                        break;
+                    };
+                    heartbeat::beat(&state, Component::UserFeed).await;
                     if is_shutting_down(&state).await {
                         let _ = ws.close(None).await;
                         return;
                     }
                     match msg {
+                        Ok(Message::Text(txt)) if txt.as_str() == "PONG" => {}
                         Ok(Message::Text(txt)) => {
-                            handle_user_event(&txt, &state, &cmd_tx).await;
+                            handle_user_event(&txt, &state, &cmd_tx, &event_tx).await;
                         }
                         Ok(Message::Ping(p)) => {
                             let _ = ws.send(Message::Pong(p)).await;
@@ -70,26 +88,24 @@ pub async fn user_ws_task(
 async fn handle_user_event(
     txt: &str,
     state: &Arc<Mutex<AppState>>,
-    _cmd_tx: &mpsc::Sender<BotCommand>,
+    _cmd_tx: &CommandSender,
+    event_tx: &mpsc::Sender<StrategyEvent>,
 ) {
     // Peek event_type
     let Ok(msg) = serde_json::from_str::<UserWebSocketMessages>(txt) else {
         return;
     };
 
-    // Only process events for the configured YES token asset_id; ignore others
-    let target_asset_id = {
-        let s = state.lock().await;
-        s.yes_token.clone().unwrap_or_default()
-    };
-    if target_asset_id.is_empty() {
+    // Only process events for the running markets' YES/NO tokens; ignore others
+    let tracked_assets: Vec<String> = state.lock().await.market_tokens();
+    if tracked_assets.is_empty() {
         // If not configured, ignore all to avoid contaminating state
         return;
     }
 
     match msg {
         UserWebSocketMessages::TradeMessage(msg) => {
-            if msg.asset_id != target_asset_id {
+            if !tracked_assets.contains(&msg.asset_id) {
                 return;
             }
             // Only act on matched/confirmed statuses to update inventory
@@ -106,6 +122,30 @@ async fn handle_user_event(
                     };
                     // Update inventory immediately
                     let mut s = state.lock().await;
+                    // Our side of the match: one of the maker orders if we were resting, else the taker
+                    let (order_id, price, matched) = match msg
+                        .maker_orders
+                        .iter()
+                        .find(|m| s.my_open_orders.contains_key(&m.order_id))
+                    {
+                        Some(maker) => (
+                            maker.order_id.clone(),
+                            maker.price.parse::<f64>().unwrap_or(0.0),
+                            maker.matched_amount.parse::<f64>().unwrap_or(size),
+                        ),
+                        None => (
+                            msg.taker_order_id.clone(),
+                            msg.price.parse::<f64>().unwrap_or(0.0),
+                            size,
+                        ),
+                    };
+                    let fill = Fill {
+                        order_id,
+                        asset_id: msg.asset_id.clone(),
+                        side: side.clone(),
+                        price,
+                        size: matched,
+                    };
                     let current = s.inventory.get(&msg.asset_id).copied().unwrap();
                     let delta = match side {
                         Side::Buy => size,
@@ -116,6 +156,8 @@ async fn handle_user_event(
                     let ts = msg.timestamp.parse::<i64>().unwrap();
                     let now_ms = now_millis();
                     println!("Trade {} filled {} milliseconds later", msg.id, now_ms - ts);
+                    drop(s);
+                    let _ = event_tx.send(StrategyEvent::Fill(fill)).await;
                     // let entry = s
                     //     .last_prices
                     //     .entry(msg.asset_id.clone())
@@ -132,13 +174,21 @@ async fn handle_user_event(
             }
         }
         UserWebSocketMessages::OrderMessage(msg) => {
-            if msg.asset_id != target_asset_id {
+            if !tracked_assets.contains(&msg.asset_id) {
                 return;
             }
             // Maintain my_open_orders map
             let mut s = state.lock().await;
+            let order_event = OrderEvent {
+                order_id: msg.id.clone(),
+                asset_id: msg.asset_id.clone(),
+                msg_type: msg.msg_type.clone(),
+            };
             match msg.msg_type.as_str() {
                 crate::modules::types::MSG_PLACEMENT | crate::modules::types::MSG_UPDATE => {
+                    if msg.msg_type == crate::modules::types::MSG_PLACEMENT {
+                        s.latency.on_placement(&msg.id);
+                    }
                     // We don't have order id mapping to our internal Order here; just track id and rough info
                     s.my_open_orders.entry(msg.id.clone()).or_insert(
                         crate::modules::types::Order {
@@ -164,10 +214,13 @@ async fn handle_user_event(
                 //     }
                 // }
                 crate::modules::types::MSG_CANCELLATION => {
-                    s.my_open_orders.remove(&msg.id);
+                    // Exchange-confirmed cancel also resolves any pending cancel for this id
+                    s.forget_order(&msg.id);
                 }
                 _ => {}
             }
+            drop(s);
+            let _ = event_tx.send(StrategyEvent::Order(order_event)).await;
         }
     }
 }
@@ -175,11 +228,3 @@ async fn handle_user_event(
 async fn is_shutting_down(state: &Arc<Mutex<AppState>>) -> bool {
     state.lock().await.shutting_down
 }
-
-fn now_millis() -> i64 {
-    use std::time::{SystemTime, UNIX_EPOCH};
-    SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .map(|d| d.as_millis() as i64)
-        .unwrap_or(0)
-}
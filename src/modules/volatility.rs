@@ -0,0 +1,195 @@
+use crate::modules::types::AppState;
+use serde::{Deserialize, Serialize};
+use std::time::Instant;
+
+// -------------------- Volatility Defaults --------------------
+const DEFAULT_FAST_HALF_LIFE_SECS: f64 = 30.0;
+const DEFAULT_SLOW_HALF_LIFE_SECS: f64 = 600.0;
+const DEFAULT_MIN_SAMPLES: u32 = 20; // no spread scaling until this many price changes
+const DEFAULT_BASELINE: f64 = 0.005; // sigma (per sqrt(minute)) at which spreads are unscaled
+const DEFAULT_MIN_MULTIPLIER: f64 = 0.5;
+const DEFAULT_MAX_MULTIPLIER: f64 = 3.0;
+const SECS_PER_MINUTE: f64 = 60.0;
+const MIN_TRADE_DISTANCE: f64 = 0.001; // caps kappa at 1000 when prints sit on the mid
+
+/// `volatility` in the config
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct VolatilityConfig {
+    pub fast_half_life_secs: f64,
+    pub slow_half_life_secs: f64,
+    pub min_samples: u32,
+    pub baseline: f64, // price stdev per sqrt(minute) that maps to a 1x spread
+    pub min_multiplier: f64,
+    pub max_multiplier: f64,
+}
+
+impl Default for VolatilityConfig {
+    fn default() -> Self {
+        Self {
+            fast_half_life_secs: DEFAULT_FAST_HALF_LIFE_SECS,
+            slow_half_life_secs: DEFAULT_SLOW_HALF_LIFE_SECS,
+            min_samples: DEFAULT_MIN_SAMPLES,
+            baseline: DEFAULT_BASELINE,
+            min_multiplier: DEFAULT_MIN_MULTIPLIER,
+            max_multiplier: DEFAULT_MAX_MULTIPLIER,
+        }
+    }
+}
+
+/// Time-decayed average of squared price changes per second. A change after `dt` seconds
+/// weighs `1 - exp(-dt / tau)`, so bursts of updates cannot dominate a quiet stretch.
+#[derive(Clone, Debug, Default)]
+struct Ewma {
+    var_per_sec: f64,
+}
+
+impl Ewma {
+    fn update(&mut self, change: f64, dt_secs: f64, half_life_secs: f64) {
+        let tau = half_life_secs / std::f64::consts::LN_2;
+        let weight = 1.0 - (-dt_secs / tau).exp();
+        // Squared change spread over the elapsed time; bounded as dt -> 0 because weight -> 0
+        let sample = change * change / dt_secs.max(f64::EPSILON);
+        self.var_per_sec = (1.0 - weight) * self.var_per_sec + weight * sample;
+    }
+}
+
+/// Realized volatility of one asset from mid changes and trade prints, and how far from the
+/// mid its trades print
+#[derive(Clone, Debug, Default)]
+pub struct VolEstimator {
+    last_mid: Option<(f64, Instant)>,
+    last_trade: Option<(f64, Instant)>,
+    fast: Ewma,
+    slow: Ewma,
+    samples: u32,
+    trade_distance: f64, // time-decayed mean |trade - mid| over the slow half-life
+    trades: u32,         // prints seen with a mid to measure them from
+}
+
+impl VolEstimator {
+    fn observe(
+        &mut self,
+        last: Option<(f64, Instant)>,
+        price: f64,
+        now: Instant,
+        cfg: &VolatilityConfig,
+    ) {
+        let Some((prev, at)) = last else {
+            return;
+        };
+        let dt = now.duration_since(at).as_secs_f64();
+        if dt <= 0.0 {
+            return;
+        }
+        self.fast.update(price - prev, dt, cfg.fast_half_life_secs);
+        self.slow.update(price - prev, dt, cfg.slow_half_life_secs);
+        self.samples += 1;
+    }
+
+    pub fn on_mid(&mut self, mid: f64, cfg: &VolatilityConfig) {
+        let now = Instant::now();
+        if self.last_mid.map(|(m, _)| m) != Some(mid) {
+            self.observe(self.last_mid, mid, now, cfg);
+            self.last_mid = Some((mid, now));
+        }
+    }
+
+    pub fn on_trade(&mut self, price: f64, cfg: &VolatilityConfig) {
+        let now = Instant::now();
+        if let Some((mid, _)) = self.last_mid {
+            let distance = (price - mid).abs();
+            let weight = match self.last_trade {
+                Some((_, at)) if self.trades > 0 => {
+                    let tau = cfg.slow_half_life_secs / std::f64::consts::LN_2;
+                    1.0 - (-now.duration_since(at).as_secs_f64() / tau).exp()
+                }
+                _ => 1.0,
+            };
+            self.trade_distance = (1.0 - weight) * self.trade_distance + weight * distance;
+            self.trades += 1;
+        }
+        self.observe(self.last_trade, price, now, cfg);
+        self.last_trade = Some((price, now));
+    }
+
+    /// (fast, slow) sigma per sqrt(minute) once warmed up
+    pub fn sigmas(&self, cfg: &VolatilityConfig) -> Option<(f64, f64)> {
+        if self.samples < cfg.min_samples {
+            return None;
+        }
+        let per_minute = |e: &Ewma| (e.var_per_sec * SECS_PER_MINUTE).sqrt();
+        Some((per_minute(&self.fast), per_minute(&self.slow)))
+    }
+
+    /// Sigma per sqrt(minute) used for quoting: the faster of the two horizons to react,
+    /// the slower one as a floor so a lull does not collapse spreads at once
+    pub fn sigma(&self, cfg: &VolatilityConfig) -> Option<f64> {
+        self.sigmas(cfg).map(|(fast, slow)| fast.max(slow))
+    }
+
+    /// Order arrival decay kappa (per 1.00 of distance from the mid) once `min_samples` trades
+    /// were measured. Fills arrive with intensity A * exp(-kappa * distance), so trade
+    /// distances from the mid are exponential with mean 1 / kappa.
+    pub fn kappa(&self, cfg: &VolatilityConfig) -> Option<f64> {
+        (self.trades >= cfg.min_samples).then(|| 1.0 / self.trade_distance.max(MIN_TRADE_DISTANCE))
+    }
+
+    /// Spread scale relative to the configured baseline; 1.0 until warmed up
+    pub fn spread_multiplier(&self, cfg: &VolatilityConfig) -> f64 {
+        match self.sigma(cfg) {
+            Some(sigma) if cfg.baseline > 0.0 => {
+                (sigma / cfg.baseline).clamp(cfg.min_multiplier, cfg.max_multiplier)
+            }
+            _ => 1.0,
+        }
+    }
+}
+
+/// Feed a mid for `asset_id` into its estimator
+pub fn observe_mid(state: &mut AppState, asset_id: &str, mid: f64) {
+    let cfg = state.market_config(asset_id).volatility.clone();
+    state
+        .volatility
+        .entry(asset_id.to_string())
+        .or_default()
+        .on_mid(mid, &cfg);
+}
+
+/// Feed a trade print for `asset_id` into its estimator
+pub fn observe_trade(state: &mut AppState, asset_id: &str, price: f64) {
+    let cfg = state.market_config(asset_id).volatility.clone();
+    state
+        .volatility
+        .entry(asset_id.to_string())
+        .or_default()
+        .on_trade(price, &cfg);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn kappa_is_one_over_the_mean_trade_distance_once_warmed_up() {
+        let cfg = VolatilityConfig::default();
+        let mut v = VolEstimator::default();
+        v.on_trade(0.52, &cfg); // no mid to measure from yet
+        v.on_mid(0.50, &cfg);
+        for i in 1..cfg.min_samples {
+            v.on_trade(if i % 2 == 0 { 0.52 } else { 0.48 }, &cfg);
+        }
+        assert_eq!(v.kappa(&cfg), None);
+        v.on_trade(0.52, &cfg);
+        let kappa = v.kappa(&cfg).unwrap();
+        assert!((kappa - 50.0).abs() < 1e-6, "kappa {}", kappa);
+
+        // Prints on the mid are capped rather than sending kappa to infinity
+        let mut v = VolEstimator::default();
+        v.on_mid(0.50, &cfg);
+        for _ in 0..cfg.min_samples {
+            v.on_trade(0.50, &cfg);
+        }
+        assert_eq!(v.kappa(&cfg), Some(1.0 / MIN_TRADE_DISTANCE));
+    }
+}
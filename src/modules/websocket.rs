@@ -1,6 +1,11 @@
+use crate::modules::heartbeat::{self, Component};
+use crate::modules::order_book::DepthUpdate;
+use crate::modules::queue;
 use crate::modules::types::{
-    AppState, BookMessage, MarketUpdate, MarketWebSocketMessages, PriceChangeMessage,
+    AppState, BookMessage, MarketUpdate, MarketWebSocketMessages, OrderSummary, PriceChangeMessage,
+    Side,
 };
+use crate::modules::volatility;
 use futures_util::{SinkExt, StreamExt};
 use serde_json::json;
 use std::sync::Arc;
@@ -8,16 +13,18 @@ use tokio::sync::{mpsc, Mutex};
 use tokio::time::{sleep, Duration};
 use tokio_tungstenite::{connect_async, tungstenite::Message};
 
+const PING_INTERVAL_SECS: u64 = 10;
+
 // -------------------- WebSocket Client --------------------
+/// One market-channel connection for every running market's tokens. When the market
+/// manager starts or stops a market, the connection is re-established with the new set.
 pub async fn websocket_client_task(
     ws_url: String,
-    asset_id: String,
     market_tx: mpsc::Sender<MarketUpdate>,
     state: Arc<Mutex<AppState>>,
 ) {
     println!("Starting websocket client task...");
-    // Keep a copy to filter incoming messages strictly to this asset only
-    let target_asset_id = asset_id.clone();
+    // Filter incoming messages strictly to the running markets' YES/NO tokens
     let mut backoff_secs = 1u64;
     loop {
         // Only break on shutdown after successful backoff iteration to avoid early exits
@@ -26,16 +33,14 @@ pub async fn websocket_client_task(
             break;
         }
         println!("Connecting to websocket...");
+        let asset_ids = state.lock().await.market_tokens();
         match connect_async(&ws_url).await {
             Ok((mut ws, _)) => {
                 // Subscribe to market channel for provided asset token IDs
-                println!(
-                    "Subscribing to market channel for asset: {}",
-                    target_asset_id
-                );
-                if !target_asset_id.is_empty() {
+                println!("Subscribing to market channel for assets: {:?}", asset_ids);
+                if !asset_ids.is_empty() {
                     // Per API, market WS expects just {"assets_ids": [...]}
-                    let sub_msg = json!({"assets_ids": [target_asset_id]});
+                    let sub_msg = json!({ "assets_ids": asset_ids });
                     let res = ws.send(Message::Text(sub_msg.to_string().into())).await;
                     if res.is_err() {
                         println!("Error sending market subscription: {:?}", res);
@@ -45,15 +50,64 @@ pub async fn websocket_client_task(
                 }
 
                 backoff_secs = 1;
-                while let Some(msg) = ws.next().await {
+                let mut ping = tokio::time::interval(Duration::from_secs(PING_INTERVAL_SECS));
+                loop {
+                    let msg = tokio::select! {
+                        _ = ping.tick() => {
+                            if state.lock().await.market_tokens() != asset_ids {
+                                println!("Market WS: running markets changed; resubscribing");
+                                let _ = ws.close(None).await;
+                                break;
+                            }
+                            // Text PING keeps the feed (and its heartbeat) alive on quiet markets
+                            let _ = ws.send(Message::Text("PING".into())).await;
+                            continue;
+                        }
+                        msg = ws.next() => msg,
+                    };
+                    let Some(msg) = msg else {
+                        break;
+                    };
+                    heartbeat::beat(&state, Component::MarketFeed).await;
                     if is_shutting_down(&state).await {
                         let _ = ws.close(None).await;
                         return;
                     }
                     match msg {
+                        Ok(Message::Text(txt)) if txt.as_str() == "PONG" => {}
                         Ok(Message::Text(txt)) => {
-                            for u in parse_update(&txt) {
-                                if u.asset_id == target_asset_id {
+                            let msgs = parse_messages(&txt);
+                            let tick_changes = tick_size_changes(&msgs);
+                            if !tick_changes.is_empty() {
+                                let mut s = state.lock().await;
+                                for (asset_id, tick) in tick_changes {
+                                    println!(
+                                        "Market WS: tick size for {} is now {}",
+                                        asset_id, tick
+                                    );
+                                    s.tick_sizes.insert(asset_id, tick);
+                                }
+                            }
+                            let prints = trade_prints(&msgs);
+                            if !prints.is_empty() {
+                                let mut s = state.lock().await;
+                                for (asset_id, price) in prints {
+                                    if asset_ids.contains(&asset_id) {
+                                        volatility::observe_trade(&mut s, &asset_id, price);
+                                    }
+                                }
+                            }
+                            let depth = depth_updates(&msgs);
+                            if !depth.is_empty() {
+                                let mut s = state.lock().await;
+                                for update in depth {
+                                    if asset_ids.iter().any(|a| a == update.asset_id()) {
+                                        queue::apply_depth(&mut s, &update);
+                                    }
+                                }
+                            }
+                            for u in best_prices(&msgs) {
+                                if asset_ids.contains(&u.asset_id) {
                                     if market_tx.send(u).await.is_err() {
                                         return;
                                     }
@@ -85,67 +139,137 @@ pub async fn websocket_client_task(
 }
 
 // -------------------- Message Parsing --------------------
-pub fn parse_update(txt: &str) -> Vec<MarketUpdate> {
-    let mut out = Vec::new();
-
+/// Decode a market-channel frame once; every event is then read from the result
+pub fn parse_messages(txt: &str) -> Vec<MarketWebSocketMessages> {
     // Try array first: market WS may send batches like `[ {...}, {...} ]`
     if let Ok(msgs) = serde_json::from_str::<Vec<MarketWebSocketMessages>>(txt) {
         println!("Parsed array of MarketWebSocketMessages: {:?}", msgs);
-        for msg in msgs {
-            push_updates_from_msg(&mut out, msg);
-        }
-        return out;
+        return msgs;
     }
 
     // Fallback: explicit array of BookMessage
     if let Ok(books) = serde_json::from_str::<Vec<BookMessage>>(txt) {
         println!("Parsed array of BookMessage: {:?}", books);
-        for book in books {
-            push_updates_from_msg(&mut out, MarketWebSocketMessages::BookMessage(book));
-        }
-        return out;
+        return books
+            .into_iter()
+            .map(MarketWebSocketMessages::BookMessage)
+            .collect();
     }
 
     // Fallback: explicit array of PriceChangeMessage
     if let Ok(price_msgs) = serde_json::from_str::<Vec<PriceChangeMessage>>(txt) {
         println!("Parsed array of PriceChangeMessage: {:?}", price_msgs);
-        for pcm in price_msgs {
-            push_updates_from_msg(&mut out, MarketWebSocketMessages::PriceChangeMessage(pcm));
-        }
-        return out;
+        return price_msgs
+            .into_iter()
+            .map(MarketWebSocketMessages::PriceChangeMessage)
+            .collect();
     }
 
     // Fallback to single message
     match serde_json::from_str::<MarketWebSocketMessages>(txt) {
         Ok(msg) => {
             println!("Parsed single MarketWebSocketMessage: {:?}", msg);
-            push_updates_from_msg(&mut out, msg);
+            vec![msg]
         }
         Err(err) => {
             // Try single BookMessage
             if let Ok(book) = serde_json::from_str::<BookMessage>(txt) {
                 println!("Parsed single BookMessage: {:?}", book);
-                push_updates_from_msg(&mut out, MarketWebSocketMessages::BookMessage(book));
-                return out;
+                return vec![MarketWebSocketMessages::BookMessage(book)];
             }
             // Try single PriceChangeMessage
             if let Ok(pcm) = serde_json::from_str::<PriceChangeMessage>(txt) {
                 println!("Parsed single PriceChangeMessage: {:?}", pcm);
-                push_updates_from_msg(&mut out, MarketWebSocketMessages::PriceChangeMessage(pcm));
-                return out;
+                return vec![MarketWebSocketMessages::PriceChangeMessage(pcm)];
             }
             println!("Error parsing update: {}", txt);
             println!("Error: {:?}", err);
+            Vec::new()
         }
     }
+}
+
+/// Best bid/ask updates from `book` and `price_change` events
+pub fn best_prices(msgs: &[MarketWebSocketMessages]) -> Vec<MarketUpdate> {
+    let mut out = Vec::new();
+    for msg in msgs {
+        push_updates_from_msg(&mut out, msg);
+    }
+    out
+}
+
+/// Extract (asset_id, new_tick_size) pairs from `tick_size_change` events
+pub fn tick_size_changes(msgs: &[MarketWebSocketMessages]) -> Vec<(String, f64)> {
+    msgs.iter()
+        .filter_map(|msg| match msg {
+            MarketWebSocketMessages::TickSizeChangeMessage(t) => t
+                .new_tick_size
+                .parse::<f64>()
+                .ok()
+                .map(|tick| (t.asset_id.clone(), tick)),
+            _ => None,
+        })
+        .collect()
+}
 
+/// Extract (asset_id, price) pairs from `last_trade_price` events
+pub fn trade_prints(msgs: &[MarketWebSocketMessages]) -> Vec<(String, f64)> {
+    msgs.iter()
+        .filter_map(|msg| match msg {
+            MarketWebSocketMessages::LastTradePriceMessage(t) => t
+                .price
+                .parse::<f64>()
+                .ok()
+                .map(|price| (t.asset_id.clone(), price)),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Extract full-depth snapshots (`book`) and level changes (`price_change`)
+pub fn depth_updates(msgs: &[MarketWebSocketMessages]) -> Vec<DepthUpdate> {
+    let levels = |orders: &[OrderSummary]| -> Vec<(f64, f64)> {
+        orders
+            .iter()
+            .filter_map(|o| Some((o.price.parse().ok()?, o.size.parse().ok()?)))
+            .collect()
+    };
+    let mut out = Vec::new();
+    for msg in msgs {
+        match msg {
+            MarketWebSocketMessages::BookMessage(book) => out.push(DepthUpdate::Snapshot {
+                bids: levels(&book.bids),
+                asks: levels(&book.asks),
+                asset_id: book.asset_id.clone(),
+            }),
+            MarketWebSocketMessages::PriceChangeMessage(pcm) => {
+                for change in &pcm.price_changes {
+                    let (Ok(price), Ok(size)) = (change.price.parse(), change.size.parse()) else {
+                        continue;
+                    };
+                    let side = if change.side.eq_ignore_ascii_case("BUY") {
+                        Side::Buy
+                    } else {
+                        Side::Sell
+                    };
+                    out.push(DepthUpdate::Level {
+                        asset_id: change.asset_id.clone(),
+                        side,
+                        price,
+                        size,
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
     out
 }
 
-fn push_updates_from_msg(out: &mut Vec<MarketUpdate>, msg: MarketWebSocketMessages) {
+fn push_updates_from_msg(out: &mut Vec<MarketUpdate>, msg: &MarketWebSocketMessages) {
     match msg {
         MarketWebSocketMessages::BookMessage(book) => {
-            let asset_id = book.asset_id;
+            let asset_id = book.asset_id.clone();
             let parse = |s: &str| {
                 let t = if s.starts_with('.') {
                     format!("0{}", s)
@@ -173,8 +297,8 @@ fn push_updates_from_msg(out: &mut Vec<MarketUpdate>, msg: MarketWebSocketMessag
             });
         }
         MarketWebSocketMessages::PriceChangeMessage(price_change_message) => {
-            for change in price_change_message.price_changes {
-                let asset_id = change.asset_id;
+            for change in &price_change_message.price_changes {
+                let asset_id = change.asset_id.clone();
                 let best_bid = change.best_bid.parse::<f64>().unwrap_or(0.0);
                 let best_ask = change.best_ask.parse::<f64>().unwrap_or(1.0);
                 let ts = price_change_message.timestamp.parse::<i64>().unwrap_or(0);
@@ -0,0 +1,114 @@
+use crate::modules::types::{AppState, Order, Side};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+// -------------------- Wind-Down Defaults --------------------
+const DEFAULT_START_MINS: f64 = 360.0; // begin scaling this long before the cutoff
+const DEFAULT_REDUCE_ONLY_MINS: f64 = 60.0;
+const DEFAULT_PULL_MINS: f64 = 5.0;
+const DEFAULT_MIN_SIZE_FACTOR: f64 = 0.25;
+const DEFAULT_MAX_SPREAD_FACTOR: f64 = 3.0;
+const PROGRESS_STEPS: f64 = 20.0; // factors move in 5% steps so quotes are not replaced constantly
+
+/// `wind_down` in the config
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WindDownConfig {
+    pub enabled: bool,          // false quotes normally up to resolution
+    pub start_mins: f64,        // sizes shrink and spreads widen from here to the cutoff
+    pub reduce_only_mins: f64,  // within this, only sell inventory we hold
+    pub pull_mins: f64,         // within this, quote nothing
+    pub min_size_factor: f64,   // order_size multiplier reached at the cutoff
+    pub max_spread_factor: f64, // edge / spread multiplier reached at the cutoff
+}
+
+impl Default for WindDownConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            start_mins: DEFAULT_START_MINS,
+            reduce_only_mins: DEFAULT_REDUCE_ONLY_MINS,
+            pull_mins: DEFAULT_PULL_MINS,
+            min_size_factor: DEFAULT_MIN_SIZE_FACTOR,
+            max_spread_factor: DEFAULT_MAX_SPREAD_FACTOR,
+        }
+    }
+}
+
+/// Resolution and game start times from market metadata
+#[derive(Clone, Debug, Default)]
+pub struct MarketSchedule {
+    pub end_date: Option<DateTime<Utc>>,
+    pub game_start: Option<DateTime<Utc>>,
+}
+
+/// How far along the wind-down a market is, as quoting adjustments
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct WindDown {
+    pub size_factor: f64,
+    pub spread_factor: f64,
+    pub reduce_only: bool,
+    pub pull: bool,
+}
+
+impl WindDown {
+    pub const NONE: WindDown = WindDown {
+        size_factor: 1.0,
+        spread_factor: 1.0,
+        reduce_only: false,
+        pull: false,
+    };
+
+    /// Apply `reduce_only`/`pull` to a quote set: only sells, funded from held inventory,
+    /// survive reduce-only
+    pub fn filter(&self, orders: &mut Vec<Order>) {
+        if self.pull {
+            orders.clear();
+        } else if self.reduce_only {
+            orders.retain(|order| order.side == Side::Sell);
+        }
+    }
+}
+
+/// Resolution time: the config's `end_date` if set, else the metadata's
+pub fn end_date(s: &AppState, yes_token: &str) -> Option<DateTime<Utc>> {
+    s.market_config(yes_token)
+        .end_date
+        .or_else(|| s.schedules.get(yes_token).and_then(|m| m.end_date))
+}
+
+/// When quoting must be wound down by: the earlier of the game start and the resolution
+pub fn cutoff(s: &AppState, yes_token: &str) -> Option<DateTime<Utc>> {
+    let game_start = s
+        .market_config(yes_token)
+        .game_start
+        .or_else(|| s.schedules.get(yes_token).and_then(|m| m.game_start));
+    match (end_date(s, yes_token), game_start) {
+        (Some(end), Some(start)) => Some(end.min(start)),
+        (end, start) => end.or(start),
+    }
+}
+
+/// Quoting adjustments for `yes_token` right now
+pub fn phase(s: &AppState, yes_token: &str) -> WindDown {
+    let config = &s.market_config(yes_token).wind_down;
+    let Some(cutoff) = cutoff(s, yes_token).filter(|_| config.enabled) else {
+        return WindDown::NONE;
+    };
+    let mins_left = (cutoff - Utc::now()).num_seconds() as f64 / 60.0;
+    if mins_left >= config.start_mins {
+        return WindDown::NONE;
+    }
+    let progress = if config.start_mins > 0.0 {
+        (1.0 - mins_left.max(0.0) / config.start_mins).clamp(0.0, 1.0)
+    } else {
+        1.0
+    };
+    let progress = (progress * PROGRESS_STEPS).round() / PROGRESS_STEPS;
+    WindDown {
+        size_factor: 1.0 - progress * (1.0 - config.min_size_factor),
+        spread_factor: 1.0 + progress * (config.max_spread_factor - 1.0),
+        reduce_only: mins_left < config.reduce_only_mins,
+        pull: mins_left < config.pull_mins,
+    }
+}